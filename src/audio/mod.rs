@@ -0,0 +1,144 @@
+mod music;
+
+use bevy::{audio::Volume, prelude::*};
+
+pub use self::music::{DEFAULT_MUSIC_FADE_S, DucksMusic, PlayMusic};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins(music::plugin);
+
+    app.register_type::<Music>();
+    app.register_type::<SoundEffect>();
+    app.register_type::<MusicVolume>();
+    app.init_resource::<MusicVolume>();
+    app.register_type::<SoundEffectVolume>();
+    app.init_resource::<SoundEffectVolume>();
+
+    app.add_systems(
+        Update,
+        apply_global_volume.run_if(
+            resource_changed::<GlobalVolume>
+                .or(resource_changed::<MusicVolume>)
+                .or(resource_changed::<SoundEffectVolume>),
+        ),
+    );
+    // Sinks are created in `PostUpdate`, with only the global volume applied.
+    app.add_systems(Last, apply_volume_to_new_sinks);
+}
+
+/// An organizational marker component that should be added to a spawned [`AudioPlayer`] if it's in the
+/// general "music" category (e.g. global background music, soundtrack).
+///
+/// This can then be used to query for and operate on sounds in that category.
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct Music;
+
+/// A music audio instance. Send [`PlayMusic`] instead to have it crossfade with whatever is
+/// already playing.
+pub fn music(handle: Handle<AudioSource>) -> impl Bundle {
+    (AudioPlayer(handle), PlaybackSettings::LOOP, Music)
+}
+
+/// An organizational marker component that should be added to a spawned [`AudioPlayer`] if it's in the
+/// general "sound effect" category (e.g. footsteps, the sound of a magic spell, a door opening).
+///
+/// This can then be used to query for and operate on sounds in that category.
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct SoundEffect;
+
+/// The volume of everything marked [`Music`], on top of [`GlobalVolume`].
+#[derive(Resource, Reflect, Debug, Clone, Copy)]
+#[reflect(Resource)]
+pub struct MusicVolume(pub Volume);
+
+impl Default for MusicVolume {
+    fn default() -> Self {
+        Self(Volume::Linear(1.0))
+    }
+}
+
+/// The volume of everything marked [`SoundEffect`], on top of [`GlobalVolume`].
+#[derive(Resource, Reflect, Debug, Clone, Copy)]
+#[reflect(Resource)]
+pub struct SoundEffectVolume(pub Volume);
+
+impl Default for SoundEffectVolume {
+    fn default() -> Self {
+        Self(Volume::Linear(1.0))
+    }
+}
+
+/// A sound effect audio instance.
+pub fn sound_effect(handle: Handle<AudioSource>) -> impl Bundle {
+    (AudioPlayer(handle), PlaybackSettings::DESPAWN, SoundEffect)
+}
+
+/// [`GlobalVolume`] doesn't apply to already-running audio entities, so this system will update them.
+/// Audio in a category is scaled by that category's volume as well.
+fn apply_global_volume(
+    global_volume: Res<GlobalVolume>,
+    music_volume: Res<MusicVolume>,
+    sound_effect_volume: Res<SoundEffectVolume>,
+    mut audio_query: Query<(
+        &PlaybackSettings,
+        &mut AudioSink,
+        Has<Music>,
+        Has<SoundEffect>,
+    )>,
+) {
+    for (playback, mut sink, is_music, is_sound_effect) in &mut audio_query {
+        let category_volume = category_volume(
+            is_music,
+            is_sound_effect,
+            &music_volume,
+            &sound_effect_volume,
+        );
+        sink.set_volume(global_volume.volume * category_volume * playback.volume);
+    }
+}
+
+/// Scales audio that just started playing by its category's volume.
+fn apply_volume_to_new_sinks(
+    global_volume: Res<GlobalVolume>,
+    music_volume: Res<MusicVolume>,
+    sound_effect_volume: Res<SoundEffectVolume>,
+    mut audio_query: Query<
+        (
+            &PlaybackSettings,
+            &mut AudioSink,
+            Has<Music>,
+            Has<SoundEffect>,
+        ),
+        Added<AudioSink>,
+    >,
+) {
+    for (playback, mut sink, is_music, is_sound_effect) in &mut audio_query {
+        if !is_music && !is_sound_effect {
+            continue;
+        }
+        let category_volume = category_volume(
+            is_music,
+            is_sound_effect,
+            &music_volume,
+            &sound_effect_volume,
+        );
+        sink.set_volume(global_volume.volume * category_volume * playback.volume);
+    }
+}
+
+fn category_volume(
+    is_music: bool,
+    is_sound_effect: bool,
+    music_volume: &MusicVolume,
+    sound_effect_volume: &SoundEffectVolume,
+) -> Volume {
+    if is_music {
+        music_volume.0
+    } else if is_sound_effect {
+        sound_effect_volume.0
+    } else {
+        Volume::Linear(1.0)
+    }
+}
@@ -0,0 +1,203 @@
+//! Background music that crossfades between tracks, keeps the place of tracks that should resume
+//! later, and ducks under dialogue voices.
+
+use bevy::{audio::Volume, prelude::*};
+
+use crate::{
+    AppSystems,
+    audio::{MusicVolume, music},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<MusicTrack>();
+    app.register_type::<DucksMusic>();
+    app.register_type::<MusicDucking>();
+    app.init_resource::<MusicDucking>();
+    app.add_event::<PlayMusic>();
+
+    // Music keeps playing while the game is paused.
+    app.add_systems(
+        Update,
+        (switch_music, fade_music)
+            .chain()
+            .in_set(AppSystems::Update),
+    );
+}
+
+/// How long a crossfade takes unless a [`PlayMusic`] says otherwise.
+pub const DEFAULT_MUSIC_FADE_S: f32 = 1.5;
+
+/// Fades the current music out and `track` in.
+#[derive(Event, Debug, Clone)]
+pub struct PlayMusic {
+    /// `None` fades out to silence.
+    pub track: Option<Handle<AudioSource>>,
+    pub fade_s: f32,
+    /// Keep the track's place when it's faded out, and carry on from there when it's asked for
+    /// again.
+    pub resume: bool,
+}
+
+impl PlayMusic {
+    pub fn track(track: Handle<AudioSource>) -> Self {
+        Self {
+            track: Some(track),
+            fade_s: DEFAULT_MUSIC_FADE_S,
+            resume: false,
+        }
+    }
+
+    pub fn silence() -> Self {
+        Self {
+            track: None,
+            fade_s: DEFAULT_MUSIC_FADE_S,
+            resume: false,
+        }
+    }
+
+    pub fn with_fade(mut self, fade_s: f32) -> Self {
+        self.fade_s = fade_s;
+        self
+    }
+
+    pub fn resuming(mut self) -> Self {
+        self.resume = true;
+        self
+    }
+}
+
+/// Music plays quieter while anything with this is around, like dialogue voice blips.
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct DucksMusic;
+
+/// How loud music is while ducked.
+const DUCKED_VOLUME: f32 = 0.4;
+/// How long it takes to duck the music, and to bring it back up.
+const DUCK_ATTACK_S: f32 = 0.1;
+const DUCK_RELEASE_S: f32 = 0.6;
+
+/// How far the music is ducked right now, from [`DUCKED_VOLUME`] to 1.
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+struct MusicDucking(f32);
+
+impl Default for MusicDucking {
+    fn default() -> Self {
+        Self(1.)
+    }
+}
+
+/// A track started by [`PlayMusic`].
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+struct MusicTrack {
+    track: AssetId<AudioSource>,
+    resume: bool,
+    /// Whether the track is fading in (or playing), rather than fading out.
+    fading_in: bool,
+    /// How loud the track is in its fade, from 0 to 1.
+    fade: f32,
+    /// How much [`Self::fade`] changes per second.
+    fade_speed: f32,
+}
+
+fn switch_music(
+    mut commands: Commands,
+    mut play_events: EventReader<PlayMusic>,
+    mut track_query: Query<(Entity, &mut MusicTrack, Option<&AudioSink>)>,
+) {
+    for event in play_events.read() {
+        let fade_speed = if event.fade_s > 0. {
+            event.fade_s.recip()
+        } else {
+            f32::MAX
+        };
+        let requested = event.track.as_ref().map(Handle::id);
+        let mut already_playing = false;
+        for (entity, mut track, sink) in &mut track_query {
+            let paused = sink.is_some_and(|sink| sink.is_paused());
+            if Some(track.track) == requested && (!paused || event.resume) {
+                already_playing = true;
+                track.fading_in = true;
+                track.fade_speed = fade_speed;
+                track.resume = event.resume;
+                if let Some(sink) = sink {
+                    sink.play();
+                }
+            } else if paused && Some(track.track) == requested {
+                // asked for from the start this time
+                commands.entity(entity).despawn();
+            } else if track.fading_in {
+                track.fading_in = false;
+                track.fade_speed = fade_speed;
+            }
+        }
+
+        if let Some(handle) = &event.track
+            && !already_playing
+        {
+            commands
+                .spawn((
+                    Name::new("Music"),
+                    music(handle.clone()),
+                    MusicTrack {
+                        track: handle.id(),
+                        resume: event.resume,
+                        fading_in: true,
+                        fade: 0.,
+                        fade_speed,
+                    },
+                ))
+                // start silent and fade in
+                .insert(PlaybackSettings::LOOP.with_volume(Volume::SILENT));
+        }
+    }
+}
+
+fn fade_music(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    global_volume: Res<GlobalVolume>,
+    music_volume: Res<MusicVolume>,
+    ducker_query: Query<(), With<DucksMusic>>,
+    mut ducking: ResMut<MusicDucking>,
+    mut track_query: Query<(
+        Entity,
+        &mut MusicTrack,
+        &mut PlaybackSettings,
+        Option<&mut AudioSink>,
+    )>,
+) {
+    let dt = time.delta_secs();
+    let duck_target = if ducker_query.is_empty() {
+        1.
+    } else {
+        DUCKED_VOLUME
+    };
+    let duck_range = 1. - DUCKED_VOLUME;
+    ducking.0 += (duck_target - ducking.0).clamp(
+        -duck_range * dt / DUCK_ATTACK_S,
+        duck_range * dt / DUCK_RELEASE_S,
+    );
+
+    for (entity, mut track, mut playback, sink) in &mut track_query {
+        let target = if track.fading_in { 1. } else { 0. };
+        let step = track.fade_speed * dt;
+        track.fade += (target - track.fade).clamp(-step, step);
+        // the category volumes in `audio` scale this, like any other playback volume
+        playback.volume = Volume::Linear(track.fade * ducking.0);
+        let Some(mut sink) = sink else {
+            continue;
+        };
+        sink.set_volume(global_volume.volume * music_volume.0 * playback.volume);
+
+        if !track.fading_in && track.fade <= 0. {
+            if track.resume {
+                sink.pause();
+            } else {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! Keep the camera on the player without showing past the edges of the map.
+
+use bevy::prelude::*;
+
+use crate::{
+    demo::{map::TileGrid, player::Player},
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        PostUpdate,
+        follow_player
+            .before(TransformSystem::TransformPropagate)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(OnExit(Screen::Gameplay), reset_camera);
+}
+
+/// How quickly the camera catches up with the player. Higher is snappier.
+const CAMERA_DECAY_RATE: f32 = 8.;
+
+fn follow_player(
+    mut camera: Single<(&mut Transform, &Projection), With<Camera2d>>,
+    player: Single<&Transform, (With<Player>, Without<Camera2d>)>,
+    grid: Single<(&TileGrid, &GlobalTransform)>,
+    time: Res<Time>,
+) {
+    let (camera_transform, projection) = &mut *camera;
+    let Projection::Orthographic(projection) = projection else {
+        return;
+    };
+    let (grid, grid_transform) = *grid;
+
+    // the player and the map share a parent, so clamp in its space and then move to the world
+    let bounds = grid.bounds();
+    let map_offset = grid_transform.translation().truncate();
+    let half_view = projection.area.half_size();
+    let target = player.translation.truncate();
+    let target = Vec2::new(
+        clamp_to_bounds(target.x, bounds.min.x, bounds.max.x, half_view.x),
+        clamp_to_bounds(target.y, bounds.min.y, bounds.max.y, half_view.y),
+    ) + map_offset;
+
+    let mut translation = camera_transform.translation.truncate();
+    translation.smooth_nudge(&target, CAMERA_DECAY_RATE, time.delta_secs());
+    camera_transform.translation = translation.extend(camera_transform.translation.z);
+}
+
+/// Keeps the view inside `min..max`, or centers it if the map is smaller than the view.
+fn clamp_to_bounds(value: f32, min: f32, max: f32, half_view: f32) -> f32 {
+    if max - min <= half_view * 2. {
+        (min + max) / 2.
+    } else {
+        value.clamp(min + half_view, max - half_view)
+    }
+}
+
+fn reset_camera(mut camera: Single<&mut Transform, With<Camera2d>>) {
+    camera.translation = Vec3::ZERO;
+}
@@ -0,0 +1,91 @@
+//! Starting conversations from the dialogue script in the overworld.
+
+use bevy::prelude::*;
+
+use crate::{
+    demo::level::LevelAssets,
+    dialogue::{DialogueAssets, DialogueScript},
+    game_flags::GameFlags,
+    screens::Screen,
+    text_boxes::{TEXTBOX_OFFSET_FROM_CENTER_Y, TextBox, TextBoxStyle, text_box},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<StartConversation>();
+
+    app.add_systems(
+        Update,
+        start_conversations.run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// Shows a conversation from the dialogue script in a new text box.
+#[derive(Event, Debug)]
+pub struct StartConversation {
+    pub conversation: String,
+    /// An entity to build the text box on, for senders that need to know when it closes. It's
+    /// despawned if the conversation can't be shown.
+    pub text_box: Option<Entity>,
+}
+
+impl StartConversation {
+    pub fn new(conversation: impl Into<String>) -> Self {
+        Self {
+            conversation: conversation.into(),
+            text_box: None,
+        }
+    }
+
+    pub fn with_text_box(mut self, text_box: Entity) -> Self {
+        self.text_box = Some(text_box);
+        self
+    }
+}
+
+fn start_conversations(
+    mut commands: Commands,
+    mut start_events: EventReader<StartConversation>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    level_assets: Res<LevelAssets>,
+    text_box_styles: Res<Assets<TextBoxStyle>>,
+    dialogue_assets: Res<DialogueAssets>,
+    dialogue_scripts: Res<Assets<DialogueScript>>,
+    flags: Res<GameFlags>,
+    camera: Single<Entity, With<Camera2d>>,
+) {
+    for event in start_events.read() {
+        let id = &event.conversation;
+        let Some(conversation) = dialogue_scripts
+            .get(&dialogue_assets.script)
+            .and_then(|script| script.conversation_for(id, &flags))
+        else {
+            warn!(
+                "Nothing to show for conversation \"{id}\", it's missing or none of its lines hold"
+            );
+            if let Some(text_box) = event.text_box {
+                commands.entity(text_box).try_despawn();
+            }
+            continue;
+        };
+        let style = text_box_styles
+            .get(&level_assets.text_box_style)
+            .cloned()
+            .unwrap_or_default();
+        let text_box_entity = event
+            .text_box
+            .unwrap_or_else(|| commands.spawn_empty().id());
+        // the camera moves around the map, so keep the text box on screen by attaching it there
+        commands.entity(text_box_entity).insert((
+            text_box(
+                TextBox::from_conversation(&conversation, &style),
+                style,
+                Vec2::new(0., TEXTBOX_OFFSET_FROM_CENTER_Y),
+                &mut meshes,
+                &mut materials,
+            ),
+            ChildOf(*camera),
+            StateScoped(Screen::Gameplay),
+        ));
+    }
+}
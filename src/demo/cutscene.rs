@@ -0,0 +1,558 @@
+//! Scripted sequences: named cutscenes authored as `.cutscenes.ron` assets, each a list of
+//! commands run one after another.
+
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    ecs::entity::Entities,
+    prelude::*,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    AppSystems, PausableSystems,
+    asset_tracking::LoadResource,
+    audio::{DEFAULT_MUSIC_FADE_S, PlayMusic, sound_effect},
+    demo::{
+        conversation::StartConversation,
+        map::{MapProperties, MapTriggerEntered, TileGrid},
+        movement::{GridMover, move_on_grid},
+    },
+    dialogue::{DialogueAssets, DialogueScript},
+    game_flags::{FlagCondition, FlagValue, GameFlags},
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<CutsceneScript>();
+    app.init_asset_loader::<CutsceneScriptLoader>();
+
+    app.register_type::<CutsceneAssets>();
+    app.load_resource::<CutsceneAssets>();
+    app.register_type::<ScreenFade>();
+    app.add_event::<PlayCutscene>();
+
+    app.add_systems(
+        Update,
+        (
+            play_trigger_cutscenes,
+            start_cutscenes,
+            run_cutscenes.before(move_on_grid),
+        )
+            .chain()
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems),
+    );
+}
+
+/// A collection of named cutscenes loaded from a `.cutscenes.ron` file.
+#[derive(Asset, TypePath, Debug)]
+pub struct CutsceneScript {
+    pub cutscenes: HashMap<String, Cutscene>,
+}
+
+/// The steps of a cutscene, with branches already worked out into jumps.
+#[derive(Debug, Clone)]
+pub struct Cutscene {
+    pub steps: Vec<CutsceneStep>,
+}
+
+#[derive(Debug, Clone)]
+pub enum CutsceneStep {
+    /// Shows a conversation from the dialogue script, and waits for its text box to close.
+    Dialogue(String),
+    /// Walks the [`GridMover`] with this [`Name`] to a tile, and waits for it to get there.
+    Move {
+        actor: String,
+        to: IVec2,
+    },
+    /// Waits this many seconds.
+    Wait(f32),
+    /// Fades the screen to this opacity of black over this many seconds, and waits for it.
+    Fade {
+        alpha: f32,
+        duration: f32,
+    },
+    PlaySound(Handle<AudioSource>),
+    /// Switches the music, without waiting for the crossfade.
+    PlayMusic(PlayMusic),
+    /// Sets a flag in [`GameFlags`], or removes it if there's no value.
+    SetFlag {
+        flag: String,
+        value: Option<FlagValue>,
+    },
+    /// Carries on from another step unless the condition holds.
+    JumpUnless {
+        condition: FlagCondition,
+        step: usize,
+    },
+    Jump(usize),
+}
+
+/// How a cutscene's commands are written out.
+#[derive(Deserialize)]
+enum CutsceneCommand {
+    Dialogue(String),
+    Move {
+        actor: String,
+        to: IVec2,
+    },
+    Wait(f32),
+    /// Fades the screen to black over this many seconds.
+    FadeOut(f32),
+    /// Fades the screen back in over this many seconds.
+    FadeIn(f32),
+    /// Path to the sound, relative to the assets folder.
+    Sound(String),
+    /// Crossfades to the music at this path, relative to the assets folder, over `fade` seconds.
+    Music {
+        path: String,
+        #[serde(default = "default_music_fade")]
+        fade: f32,
+    },
+    /// Fades the music out over this many seconds.
+    StopMusic(f32),
+    /// Sets a flag to `true`.
+    SetFlag(String),
+    ClearFlag(String),
+    /// Sets a flag to any value, like `Set("gold", 10)`.
+    Set(String, FlagValue),
+    /// Runs `then` if the condition holds and `else` if it doesn't. See [`FlagCondition`] for how
+    /// conditions are written.
+    If {
+        condition: FlagCondition,
+        #[serde(default)]
+        then: Vec<CutsceneCommand>,
+        #[serde(default, rename = "else")]
+        otherwise: Vec<CutsceneCommand>,
+    },
+}
+
+fn default_music_fade() -> f32 {
+    DEFAULT_MUSIC_FADE_S
+}
+
+/// Turns commands into steps, nested branches included.
+fn compile(
+    commands: Vec<CutsceneCommand>,
+    steps: &mut Vec<CutsceneStep>,
+    load_context: &mut LoadContext,
+) {
+    for command in commands {
+        match command {
+            CutsceneCommand::Dialogue(conversation) => {
+                steps.push(CutsceneStep::Dialogue(conversation));
+            }
+            CutsceneCommand::Move { actor, to } => steps.push(CutsceneStep::Move { actor, to }),
+            CutsceneCommand::Wait(seconds) => steps.push(CutsceneStep::Wait(seconds)),
+            CutsceneCommand::FadeOut(duration) => steps.push(CutsceneStep::Fade {
+                alpha: 1.,
+                duration,
+            }),
+            CutsceneCommand::FadeIn(duration) => steps.push(CutsceneStep::Fade {
+                alpha: 0.,
+                duration,
+            }),
+            CutsceneCommand::Sound(path) => {
+                steps.push(CutsceneStep::PlaySound(load_context.load(path)));
+            }
+            CutsceneCommand::Music { path, fade } => {
+                steps.push(CutsceneStep::PlayMusic(
+                    PlayMusic::track(load_context.load(path)).with_fade(fade),
+                ));
+            }
+            CutsceneCommand::StopMusic(fade) => {
+                steps.push(CutsceneStep::PlayMusic(
+                    PlayMusic::silence().with_fade(fade),
+                ));
+            }
+            CutsceneCommand::SetFlag(flag) => steps.push(CutsceneStep::SetFlag {
+                flag,
+                value: Some(FlagValue::Bool(true)),
+            }),
+            CutsceneCommand::ClearFlag(flag) => {
+                steps.push(CutsceneStep::SetFlag { flag, value: None });
+            }
+            CutsceneCommand::Set(flag, value) => steps.push(CutsceneStep::SetFlag {
+                flag,
+                value: Some(value),
+            }),
+            CutsceneCommand::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                // the jump targets get filled in once the branches are in place
+                let branch = steps.len();
+                steps.push(CutsceneStep::Jump(0));
+                compile(then, steps, load_context);
+                let skip_else = steps.len();
+                steps.push(CutsceneStep::Jump(0));
+                steps[branch] = CutsceneStep::JumpUnless {
+                    condition,
+                    step: steps.len(),
+                };
+                compile(otherwise, steps, load_context);
+                steps[skip_else] = CutsceneStep::Jump(steps.len());
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct CutsceneScriptFile {
+    cutscenes: HashMap<String, Vec<CutsceneCommand>>,
+}
+
+#[derive(Default)]
+struct CutsceneScriptLoader;
+
+#[derive(Debug, Error)]
+enum CutsceneScriptLoaderError {
+    #[error("could not read cutscene script: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse cutscene script: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for CutsceneScriptLoader {
+    type Asset = CutsceneScript;
+    type Settings = ();
+    type Error = CutsceneScriptLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let file: CutsceneScriptFile = ron::de::from_bytes(&bytes)?;
+        let cutscenes = file
+            .cutscenes
+            .into_iter()
+            .map(|(name, commands)| {
+                let mut steps = Vec::new();
+                compile(commands, &mut steps, load_context);
+                (name, Cutscene { steps })
+            })
+            .collect();
+        Ok(CutsceneScript { cutscenes })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["cutscenes.ron"]
+    }
+}
+
+/// The game's cutscene scripts. Like [`DialogueAssets`], look the script up whenever it's
+/// needed so hot reloads are picked up.
+#[derive(Resource, Asset, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct CutsceneAssets {
+    #[dependency]
+    pub script: Handle<CutsceneScript>,
+}
+
+impl FromWorld for CutsceneAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            script: assets.load("cutscenes/demo.cutscenes.ron"),
+        }
+    }
+}
+
+/// Starts a cutscene from the cutscene script, unless one is already playing.
+#[derive(Event, Debug)]
+pub struct PlayCutscene {
+    pub cutscene: String,
+}
+
+/// Plays the cutscene named by a map trigger's `cutscene` property when the player walks in.
+fn play_trigger_cutscenes(
+    mut entered_events: EventReader<MapTriggerEntered>,
+    trigger_query: Query<&MapProperties>,
+    mut play_events: EventWriter<PlayCutscene>,
+) {
+    for event in entered_events.read() {
+        if let Ok(properties) = trigger_query.get(event.trigger)
+            && let Some(cutscene) = properties.get_str("cutscene")
+        {
+            play_events.write(PlayCutscene {
+                cutscene: cutscene.to_string(),
+            });
+        }
+    }
+}
+
+/// A cutscene being played, one step at a time.
+#[derive(Component)]
+pub struct CutsceneRunner {
+    name: String,
+    steps: Vec<CutsceneStep>,
+    step: usize,
+    state: StepState,
+}
+
+/// Progress on the current step.
+#[derive(Default)]
+enum StepState {
+    #[default]
+    Starting,
+    Dialogue {
+        text_box: Entity,
+    },
+    Moving {
+        actor: Entity,
+        stalled_s: f32,
+    },
+    Waiting {
+        remaining_s: f32,
+    },
+    Fading {
+        from: f32,
+        elapsed_s: f32,
+    },
+}
+
+/// How long an actor can be stuck on a `Move` before the cutscene gives up on it.
+const MOVE_STALL_TIMEOUT: f32 = 2.;
+
+fn start_cutscenes(
+    mut commands: Commands,
+    mut play_events: EventReader<PlayCutscene>,
+    cutscene_assets: Res<CutsceneAssets>,
+    cutscene_scripts: Res<Assets<CutsceneScript>>,
+    runner_query: Query<(), With<CutsceneRunner>>,
+) {
+    let mut playing = !runner_query.is_empty();
+    for event in play_events.read() {
+        let name = &event.cutscene;
+        if playing {
+            debug!("Not playing cutscene \"{name}\" while another one is playing");
+            continue;
+        }
+        let Some(cutscene) = cutscene_scripts
+            .get(&cutscene_assets.script)
+            .and_then(|script| script.cutscenes.get(name))
+        else {
+            warn!("Missing cutscene \"{name}\"");
+            continue;
+        };
+        commands.spawn((
+            Name::new(format!("Cutscene \"{name}\"")),
+            CutsceneRunner {
+                name: name.clone(),
+                steps: cutscene.steps.clone(),
+                step: 0,
+                state: StepState::Starting,
+            },
+            StateScoped(Screen::Gameplay),
+        ));
+        playing = true;
+    }
+}
+
+/// A black overlay covering the whole screen, text boxes included.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct ScreenFade;
+
+fn screen_fade() -> impl Bundle {
+    (
+        Name::new("Screen Fade"),
+        ScreenFade,
+        Node {
+            width: Val::Percent(100.),
+            height: Val::Percent(100.),
+            position_type: PositionType::Absolute,
+            ..default()
+        },
+        BackgroundColor(Color::NONE),
+        Pickable::IGNORE,
+        StateScoped(Screen::Gameplay),
+    )
+}
+
+fn run_cutscenes(
+    mut commands: Commands,
+    mut runner_query: Query<(Entity, &mut CutsceneRunner)>,
+    mut actor_query: Query<(Entity, &Name, &mut GridMover)>,
+    mut fade_query: Query<&mut BackgroundColor, With<ScreenFade>>,
+    grid_query: Query<&TileGrid>,
+    entities: &Entities,
+    dialogue_assets: Res<DialogueAssets>,
+    dialogue_scripts: Res<Assets<DialogueScript>>,
+    mut conversation_events: EventWriter<StartConversation>,
+    mut music_events: EventWriter<PlayMusic>,
+    mut flags: ResMut<GameFlags>,
+    time: Res<Time>,
+) {
+    for (entity, mut runner) in &mut runner_query {
+        let runner = &mut *runner;
+        // Run steps until one has to wait for something.
+        loop {
+            let Some(step) = runner.steps.get(runner.step) else {
+                debug!("Cutscene \"{}\" finished", runner.name);
+                commands.entity(entity).despawn();
+                break;
+            };
+            let state = std::mem::take(&mut runner.state);
+            let next_state = match (step, state) {
+                (CutsceneStep::Dialogue(id), StepState::Starting) => {
+                    let exists = dialogue_scripts
+                        .get(&dialogue_assets.script)
+                        .is_some_and(|script| script.conversation(id).is_some());
+                    if exists {
+                        // spawned here so the step only waits on its own text box
+                        let text_box = commands.spawn(StateScoped(Screen::Gameplay)).id();
+                        conversation_events
+                            .write(StartConversation::new(id.clone()).with_text_box(text_box));
+                        Some(StepState::Dialogue { text_box })
+                    } else {
+                        warn!(
+                            "Cutscene \"{}\" skipped missing conversation \"{id}\"",
+                            runner.name
+                        );
+                        None
+                    }
+                }
+                // The text box is despawned once it has closed.
+                (CutsceneStep::Dialogue(_), StepState::Dialogue { text_box }) => entities
+                    .contains(text_box)
+                    .then_some(StepState::Dialogue { text_box }),
+                (CutsceneStep::Move { actor, .. }, StepState::Starting) => {
+                    match actor_query
+                        .iter()
+                        .find(|(_, name, _)| name.as_str() == actor)
+                    {
+                        Some((actor, ..)) => Some(StepState::Moving {
+                            actor,
+                            stalled_s: 0.,
+                        }),
+                        None => {
+                            warn!("Cutscene \"{}\" has no actor \"{actor}\"", runner.name);
+                            None
+                        }
+                    }
+                }
+                (CutsceneStep::Move { to, .. }, StepState::Moving { actor, stalled_s }) => {
+                    match (actor_query.get_mut(actor), grid_query.single()) {
+                        (Ok((_, _, mut mover)), Ok(grid)) => {
+                            if mover.position == *to && !mover.is_moving() {
+                                mover.intent = None;
+                                None
+                            } else if stalled_s >= MOVE_STALL_TIMEOUT {
+                                warn!("Cutscene \"{}\" gave up on a stuck actor", runner.name);
+                                mover.intent = None;
+                                None
+                            } else {
+                                mover.intent = step_towards(grid, mover.position, *to);
+                                let stalled_s = if mover.is_moving() {
+                                    0.
+                                } else {
+                                    stalled_s + time.delta_secs()
+                                };
+                                Some(StepState::Moving { actor, stalled_s })
+                            }
+                        }
+                        // the actor or the map went away
+                        _ => None,
+                    }
+                }
+                (CutsceneStep::Wait(seconds), StepState::Starting) => Some(StepState::Waiting {
+                    remaining_s: *seconds,
+                }),
+                (CutsceneStep::Wait(_), StepState::Waiting { remaining_s }) => {
+                    let remaining_s = remaining_s - time.delta_secs();
+                    (remaining_s > 0.).then_some(StepState::Waiting { remaining_s })
+                }
+                (CutsceneStep::Fade { .. }, StepState::Starting) => {
+                    let from = match fade_query.single() {
+                        Ok(color) => color.0.alpha(),
+                        Err(_) => {
+                            commands.spawn(screen_fade());
+                            0.
+                        }
+                    };
+                    Some(StepState::Fading {
+                        from,
+                        elapsed_s: 0.,
+                    })
+                }
+                (CutsceneStep::Fade { alpha, duration }, StepState::Fading { from, elapsed_s }) => {
+                    let elapsed_s = elapsed_s + time.delta_secs();
+                    let t = if *duration > 0. {
+                        (elapsed_s / duration).min(1.)
+                    } else {
+                        1.
+                    };
+                    if let Ok(mut color) = fade_query.single_mut() {
+                        color.0 = Color::BLACK.with_alpha(from.lerp(*alpha, t));
+                    }
+                    (t < 1.).then_some(StepState::Fading { from, elapsed_s })
+                }
+                (CutsceneStep::PlaySound(sound), _) => {
+                    commands.spawn(sound_effect(sound.clone()));
+                    None
+                }
+                (CutsceneStep::PlayMusic(event), _) => {
+                    music_events.write(event.clone());
+                    None
+                }
+                (CutsceneStep::SetFlag { flag, value }, _) => {
+                    match value {
+                        Some(value) => flags.set(flag.clone(), value.clone()),
+                        None => flags.remove(flag),
+                    }
+                    None
+                }
+                (CutsceneStep::JumpUnless { condition, step }, _) => {
+                    if !condition.holds(&flags) {
+                        runner.step = *step;
+                        continue;
+                    }
+                    None
+                }
+                (CutsceneStep::Jump(step), _) => {
+                    runner.step = *step;
+                    continue;
+                }
+                // the state always matches the step it was made for
+                (_, state) => Some(state),
+            };
+            match next_state {
+                Some(state) => {
+                    runner.state = state;
+                    break;
+                }
+                None => runner.step += 1,
+            }
+        }
+    }
+}
+
+/// Which way to step to get closer to `to`, going around solid tiles where it's easy.
+fn step_towards(grid: &TileGrid, from: IVec2, to: IVec2) -> Option<IVec2> {
+    let delta = to - from;
+    let horizontal = IVec2::new(delta.x.signum(), 0);
+    let vertical = IVec2::new(0, delta.y.signum());
+    let (first, second) = if delta.x.abs() >= delta.y.abs() {
+        (horizontal, vertical)
+    } else {
+        (vertical, horizontal)
+    };
+    [first, second]
+        .into_iter()
+        .find(|&direction| direction != IVec2::ZERO && !grid.is_solid(from + direction))
+        .or((first != IVec2::ZERO).then_some(first))
+}
+
+/// Whether a cutscene is playing, for holding off the player's own input.
+pub fn cutscene_playing(runner_query: Query<(), With<CutsceneRunner>>) -> bool {
+    !runner_query.is_empty()
+}
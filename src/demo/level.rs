@@ -2,27 +2,144 @@
 
 use bevy::prelude::*;
 
-use crate::{screens::Screen, text_boxes::text_box};
+use crate::{
+    asset_tracking::LoadResource,
+    audio::PlayMusic,
+    demo::{
+        conversation::StartConversation,
+        map::{
+            MapObjectKind, MapTrigger, MapTriggerEntered, TileGrid, TileMap, map_trigger, tile_map,
+        },
+        npc::npc,
+        player::player,
+    },
+    game_flags::GameFlags,
+    screens::Screen,
+    text_boxes::{TextBoxChoiceMade, TextBoxClosed, TextBoxFinished, TextBoxOpened, TextBoxStyle},
+};
 
-pub(super) fn plugin(_app: &mut App) {}
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<LevelAssets>();
+    app.load_resource::<LevelAssets>();
+
+    app.add_systems(
+        Update,
+        (
+            log_text_box_lifecycle,
+            log_dialogue_choices,
+            log_finished_dialogue,
+            log_map_triggers,
+        )
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+#[derive(Resource, Asset, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct LevelAssets {
+    #[dependency]
+    pub map: Handle<TileMap>,
+    #[dependency]
+    pub text_box_style: Handle<TextBoxStyle>,
+    #[dependency]
+    music: Handle<AudioSource>,
+}
+
+impl FromWorld for LevelAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            map: assets.load("maps/overworld.tmj"),
+            text_box_style: assets.load("text_boxes/default.text_box.ron"),
+            music: assets.load("audio/music/overworld.wav"),
+        }
+    }
+}
+
+const INTRO_CONVERSATION: &str = "intro";
+/// Set once the intro has played, so loading a save doesn't play it again.
+const SAW_INTRO_FLAG: &str = "saw_intro";
 
 /// A system that spawns the main level.
 pub fn spawn_level(
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    time: Res<Time>,
+    level_assets: Res<LevelAssets>,
+    tile_maps: Res<Assets<TileMap>>,
+    mut flags: ResMut<GameFlags>,
+    mut conversation_events: EventWriter<StartConversation>,
+    mut music_events: EventWriter<PlayMusic>,
 ) {
-    commands.spawn((
+    let mut level = commands.spawn((
         Name::new("Level"),
         Transform::default(),
         Visibility::default(),
         StateScoped(Screen::Gameplay),
-        text_box(
-            vec!["yo".to_string()],
-            time.elapsed_secs(),
-            &mut meshes,
-            &mut materials,
-        ),
     ));
+    if let Some(map) = tile_maps.get(&level_assets.map) {
+        let grid = TileGrid::new(map);
+        level.with_children(|parent| {
+            parent.spawn(tile_map(map));
+            parent.spawn(player(map.player_start(), &grid));
+            for object in &map.objects {
+                match object.kind {
+                    MapObjectKind::Npc => {
+                        parent.spawn(npc(object, &grid));
+                    }
+                    MapObjectKind::Trigger => {
+                        parent.spawn(map_trigger(object));
+                    }
+                    // already part of the grid
+                    MapObjectKind::Collision => {}
+                }
+            }
+        });
+    } else {
+        warn!("Missing overworld map");
+    }
+
+    // picks up where it left off when coming back from the title screen
+    music_events.write(PlayMusic::track(level_assets.music.clone()).resuming());
+
+    if !flags.is_set(SAW_INTRO_FLAG) {
+        flags.set(SAW_INTRO_FLAG, true);
+        conversation_events.write(StartConversation::new(INTRO_CONVERSATION));
+    }
+}
+
+fn log_text_box_lifecycle(
+    mut opened_events: EventReader<TextBoxOpened>,
+    mut closed_events: EventReader<TextBoxClosed>,
+) {
+    for event in opened_events.read() {
+        debug!("Text box {} opened", event.text_box);
+    }
+    for event in closed_events.read() {
+        debug!("Text box {} closed", event.text_box);
+    }
+}
+
+fn log_dialogue_choices(mut choice_events: EventReader<TextBoxChoiceMade>) {
+    for event in choice_events.read() {
+        info!(
+            "Picked \"{}\" in text box {}",
+            event.choice_id, event.text_box
+        );
+    }
+}
+
+fn log_finished_dialogue(mut finished_events: EventReader<TextBoxFinished>) {
+    for event in finished_events.read() {
+        info!("Dialogue finished in text box {}", event.text_box);
+    }
+}
+
+fn log_map_triggers(
+    mut entered_events: EventReader<MapTriggerEntered>,
+    trigger_query: Query<&Name, With<MapTrigger>>,
+) {
+    for event in entered_events.read() {
+        if let Ok(name) = trigger_query.get(event.trigger) {
+            info!("Entered map trigger \"{name}\"");
+        }
+    }
 }
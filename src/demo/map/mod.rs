@@ -0,0 +1,318 @@
+//! Grid-based overworld maps, made in Tiled.
+
+mod tiled;
+
+use std::collections::HashMap;
+
+use bevy::{ecs::spawn::SpawnIter, prelude::*};
+
+use crate::{
+    AppSystems, PausableSystems,
+    demo::{movement::GridMover, player::Player},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<TileMap>();
+    app.add_plugins(tiled::plugin);
+
+    app.register_type::<TileGrid>();
+    app.register_type::<MapProperties>();
+    app.register_type::<MapTrigger>();
+    app.add_event::<MapTriggerEntered>();
+
+    app.add_systems(
+        Update,
+        detect_map_triggers
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems),
+    );
+}
+
+/// A rectangular grid of tiles. Tile positions count columns from the left and rows from the
+/// top, the same way the map is written out.
+#[derive(Asset, TypePath, Debug, Clone)]
+pub struct TileMap {
+    /// Width and height, in tiles.
+    pub size: UVec2,
+    /// Width and height of a tile, in pixels.
+    pub tile_size: f32,
+    pub tile_kinds: Vec<TileKind>,
+    /// Drawn from the bottom up.
+    pub layers: Vec<TileLayer>,
+    pub objects: Vec<MapObject>,
+    /// Named places to put things. There's always one for [`Self::PLAYER_SPAWN`].
+    pub spawn_points: HashMap<String, IVec2>,
+    pub properties: MapProperties,
+}
+
+impl TileMap {
+    /// The spawn point the player starts on.
+    pub const PLAYER_SPAWN: &str = "player";
+
+    pub fn player_start(&self) -> IVec2 {
+        self.spawn_points[Self::PLAYER_SPAWN]
+    }
+
+    pub fn tile(&self, layer: usize, position: IVec2) -> Option<&TileKind> {
+        let index = tile_index(self.size, position)?;
+        self.layers[layer].tiles[index].map(|kind| &self.tile_kinds[kind])
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TileLayer {
+    /// Indices into [`TileMap::tile_kinds`], row by row. Empty spots are `None`.
+    pub tiles: Vec<Option<usize>>,
+    /// Hidden layers aren't drawn, but still block movement.
+    pub visible: bool,
+    /// Whether every tile on this layer blocks movement, whatever its kind.
+    pub solid: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct TileKind {
+    /// Tints [`Self::sprite`] if there is one.
+    pub color: Color,
+    pub sprite: Option<TileSprite>,
+    /// Whether the tile blocks movement.
+    pub solid: bool,
+}
+
+/// A tile drawn from a tileset image.
+#[derive(Debug, Clone)]
+pub struct TileSprite {
+    pub image: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+    pub index: usize,
+    pub flip_x: bool,
+    pub flip_y: bool,
+}
+
+/// Something placed on the map other than tiles, covering one or more of them.
+#[derive(Debug, Clone)]
+pub struct MapObject {
+    pub kind: MapObjectKind,
+    pub name: String,
+    /// The top left tile covered by the object.
+    pub position: IVec2,
+    /// Width and height, in tiles.
+    pub size: UVec2,
+    pub properties: MapProperties,
+}
+
+impl MapObject {
+    /// The tiles covered by the object, with `max` inclusive.
+    pub fn area(&self) -> IRect {
+        IRect::from_corners(
+            self.position,
+            self.position + self.size.as_ivec2() - IVec2::ONE,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapObjectKind {
+    /// A character standing on the map, see [`npc`](crate::demo::npc::npc).
+    Npc,
+    /// An area that sends [`MapTriggerEntered`] when the player walks in.
+    Trigger,
+    /// An area that blocks movement.
+    Collision,
+}
+
+/// Custom values set on a map or one of its objects, for whatever needs them.
+#[derive(Component, Reflect, Debug, Clone, Default)]
+#[reflect(Component)]
+pub struct MapProperties(pub HashMap<String, MapProperty>);
+
+impl MapProperties {
+    pub fn get(&self, name: &str) -> Option<&MapProperty> {
+        self.0.get(name)
+    }
+
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        match self.get(name)? {
+            MapProperty::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_color(&self, name: &str) -> Option<Color> {
+        match self.get(name)? {
+            MapProperty::Color(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Reflect, Debug, Clone, PartialEq)]
+pub enum MapProperty {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Color(Color),
+}
+
+fn tile_index(size: UVec2, position: IVec2) -> Option<usize> {
+    let in_bounds = position.x >= 0
+        && position.y >= 0
+        && (position.x as u32) < size.x
+        && (position.y as u32) < size.y;
+    in_bounds.then(|| position.y as usize * size.x as usize + position.x as usize)
+}
+
+/// The layout of a spawned [`TileMap`], for working out where things are and where they can go.
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+pub struct TileGrid {
+    pub size: UVec2,
+    pub tile_size: f32,
+    solid: Vec<bool>,
+}
+
+impl TileGrid {
+    pub fn new(map: &TileMap) -> Self {
+        let tile_count = map.size.x as usize * map.size.y as usize;
+        let mut solid: Vec<bool> = (0..tile_count)
+            .map(|index| {
+                let mut tiles = map
+                    .layers
+                    .iter()
+                    .filter_map(|layer| {
+                        let kind = layer.tiles[index]?;
+                        Some(layer.solid || map.tile_kinds[kind].solid)
+                    })
+                    .peekable();
+                // spots with no tile at all are solid too
+                tiles.peek().is_none() || tiles.any(|solid| solid)
+            })
+            .collect();
+        for object in &map.objects {
+            if object.kind != MapObjectKind::Collision {
+                continue;
+            }
+            let area = object.area();
+            for y in area.min.y..=area.max.y {
+                for x in area.min.x..=area.max.x {
+                    if let Some(index) = tile_index(map.size, IVec2::new(x, y)) {
+                        solid[index] = true;
+                    }
+                }
+            }
+        }
+        Self {
+            size: map.size,
+            tile_size: map.tile_size,
+            solid,
+        }
+    }
+
+    /// Empty tiles and anything off the edge of the map count as solid.
+    pub fn is_solid(&self, position: IVec2) -> bool {
+        tile_index(self.size, position).is_none_or(|index| self.solid[index])
+    }
+
+    /// The center of the tile at `position`. The map is centered on its own [`Transform`].
+    pub fn tile_to_world(&self, position: IVec2) -> Vec2 {
+        let top_left = Vec2::new(-(self.size.x as f32 - 1.0), self.size.y as f32 - 1.0) / 2.0;
+        (top_left + Vec2::new(position.x as f32, -position.y as f32)) * self.tile_size
+    }
+
+    /// The area covered by the map.
+    pub fn bounds(&self) -> Rect {
+        Rect::from_center_size(Vec2::ZERO, self.size.as_vec2() * self.tile_size)
+    }
+}
+
+const TILE_Z: f32 = 0.;
+/// How far above the layer below each tile layer is drawn.
+const TILE_LAYER_Z_STEP: f32 = 0.01;
+
+pub fn tile_map(map: &TileMap) -> impl Bundle {
+    let grid = TileGrid::new(map);
+    let tiles: Vec<_> = map
+        .layers
+        .iter()
+        .enumerate()
+        .filter(|(_, layer)| layer.visible)
+        .flat_map(|(layer, _)| {
+            (0..map.size.y as i32)
+                .flat_map(|y| (0..map.size.x as i32).map(move |x| IVec2::new(x, y)))
+                .map(move |position| (layer, position))
+        })
+        .filter_map(|(layer, position)| {
+            let kind = map.tile(layer, position)?;
+            let z = TILE_Z + layer as f32 * TILE_LAYER_Z_STEP;
+            Some((
+                tile_sprite(kind, map.tile_size),
+                Transform::from_translation(grid.tile_to_world(position).extend(z)),
+            ))
+        })
+        .collect();
+    (
+        Name::new("Tile Map"),
+        grid,
+        map.properties.clone(),
+        Transform::default(),
+        Visibility::default(),
+        Children::spawn(SpawnIter(tiles.into_iter())),
+    )
+}
+
+fn tile_sprite(kind: &TileKind, tile_size: f32) -> Sprite {
+    let Some(sprite) = &kind.sprite else {
+        return Sprite::from_color(kind.color, Vec2::splat(tile_size));
+    };
+    Sprite {
+        image: sprite.image.clone(),
+        texture_atlas: Some(TextureAtlas {
+            layout: sprite.layout.clone(),
+            index: sprite.index,
+        }),
+        color: kind.color,
+        custom_size: Some(Vec2::splat(tile_size)),
+        flip_x: sprite.flip_x,
+        flip_y: sprite.flip_y,
+        ..default()
+    }
+}
+
+/// An area of the map that sends [`MapTriggerEntered`] when the player walks into it.
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+pub struct MapTrigger {
+    /// The tiles covered by the trigger, with `max` inclusive.
+    pub area: IRect,
+    player_inside: bool,
+}
+
+#[derive(Event, Debug)]
+pub struct MapTriggerEntered {
+    pub trigger: Entity,
+}
+
+pub fn map_trigger(object: &MapObject) -> impl Bundle {
+    (
+        Name::new(object.name.clone()),
+        MapTrigger {
+            area: object.area(),
+            player_inside: false,
+        },
+        object.properties.clone(),
+    )
+}
+
+fn detect_map_triggers(
+    player: Single<&GridMover, With<Player>>,
+    mut trigger_query: Query<(Entity, &mut MapTrigger)>,
+    mut entered_events: EventWriter<MapTriggerEntered>,
+) {
+    for (entity, mut trigger) in &mut trigger_query {
+        let inside = trigger.area.contains(player.position);
+        if inside && !trigger.player_inside {
+            entered_events.write(MapTriggerEntered { trigger: entity });
+        }
+        trigger.player_inside = inside;
+    }
+}
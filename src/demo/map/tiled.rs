@@ -0,0 +1,486 @@
+//! Loading [`TileMap`]s from maps made in [Tiled](https://www.mapeditor.org/), saved as JSON
+//! (`.tmj`). Tilesets can be embedded or saved next to the map as `.tsj` files.
+//!
+//! Object types (or classes) pick what an object becomes: `spawn` for a
+//! [spawn point](TileMap::spawn_points), `npc`, `trigger` or `collision`. Tiles and tile layers
+//! with a `solid` property set to `true` block movement.
+
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{
+        AssetLoader, AssetPath, LoadContext, ParseAssetPathError, ReadAssetBytesError, io::Reader,
+    },
+    image::{ImageLoaderSettings, ImageSampler},
+    prelude::*,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::{
+    MapObject, MapObjectKind, MapProperties, MapProperty, TileGrid, TileKind, TileLayer, TileMap,
+    TileSprite,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset_loader::<TiledMapLoader>();
+}
+
+const FLIPPED_HORIZONTALLY: u32 = 0x8000_0000;
+const FLIPPED_VERTICALLY: u32 = 0x4000_0000;
+/// Diagonal and hexagonal flips aren't supported, so those tiles are drawn unrotated.
+const FLIP_FLAGS: u32 = 0xf000_0000;
+
+#[derive(Deserialize)]
+struct TiledMap {
+    orientation: String,
+    width: u32,
+    height: u32,
+    tilewidth: u32,
+    tileheight: u32,
+    #[serde(default)]
+    infinite: bool,
+    layers: Vec<TiledLayer>,
+    tilesets: Vec<TiledTilesetRef>,
+    #[serde(default)]
+    properties: Vec<TiledProperty>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum TiledLayer {
+    TileLayer {
+        name: String,
+        width: u32,
+        height: u32,
+        /// Only uncompressed CSV data is supported, which is a list of tile IDs.
+        data: serde_json::Value,
+        #[serde(default = "default_true")]
+        visible: bool,
+        #[serde(default)]
+        properties: Vec<TiledProperty>,
+    },
+    ObjectGroup {
+        objects: Vec<TiledObject>,
+    },
+    Group {
+        layers: Vec<TiledLayer>,
+    },
+    ImageLayer {},
+}
+
+#[derive(Deserialize)]
+struct TiledObject {
+    #[serde(default)]
+    name: String,
+    /// Called `class` by some versions of Tiled.
+    #[serde(default, rename = "type")]
+    kind: String,
+    #[serde(default)]
+    class: String,
+    x: f32,
+    y: f32,
+    #[serde(default)]
+    width: f32,
+    #[serde(default)]
+    height: f32,
+    /// Set on tile objects, which are placed by their bottom left corner.
+    gid: Option<u32>,
+    #[serde(default)]
+    properties: Vec<TiledProperty>,
+}
+
+#[derive(Deserialize)]
+struct TiledTilesetRef {
+    firstgid: u32,
+    source: Option<String>,
+    #[serde(flatten)]
+    tileset: Option<TiledTileset>,
+}
+
+#[derive(Deserialize)]
+struct TiledTileset {
+    tilewidth: u32,
+    tileheight: u32,
+    tilecount: u32,
+    columns: u32,
+    /// Missing for image collection tilesets, which aren't supported.
+    image: Option<String>,
+    #[serde(default)]
+    margin: u32,
+    #[serde(default)]
+    spacing: u32,
+    #[serde(default)]
+    tiles: Vec<TiledTile>,
+}
+
+#[derive(Deserialize)]
+struct TiledTile {
+    id: u32,
+    #[serde(default)]
+    properties: Vec<TiledProperty>,
+}
+
+#[derive(Deserialize)]
+struct TiledProperty {
+    name: String,
+    #[serde(default, rename = "type")]
+    kind: String,
+    value: serde_json::Value,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn map_properties(properties: Vec<TiledProperty>) -> MapProperties {
+    MapProperties(
+        properties
+            .into_iter()
+            .filter_map(|property| {
+                use serde_json::Value;
+                let value = match (property.kind.as_str(), property.value) {
+                    ("color", Value::String(color)) => MapProperty::Color(tiled_color(&color)?),
+                    (_, Value::Bool(value)) => MapProperty::Bool(value),
+                    (_, Value::Number(value)) => match value.as_i64() {
+                        Some(value) => MapProperty::Int(value),
+                        None => MapProperty::Float(value.as_f64()?),
+                    },
+                    (_, Value::String(value)) => MapProperty::String(value),
+                    // class properties have no equivalent
+                    _ => return None,
+                };
+                Some((property.name, value))
+            })
+            .collect(),
+    )
+}
+
+/// Tiled writes colors as `#AARRGGBB`, or `#RRGGBB` when they're opaque.
+fn tiled_color(color: &str) -> Option<Color> {
+    let hex = color.strip_prefix('#')?;
+    let argb = match hex.len() {
+        6 => u32::from_str_radix(hex, 16).ok()? | 0xff00_0000,
+        8 => u32::from_str_radix(hex, 16).ok()?,
+        _ => return None,
+    };
+    let [alpha, red, green, blue] = argb.to_be_bytes();
+    Some(Color::srgba_u8(red, green, blue, alpha))
+}
+
+fn is_solid(properties: &[TiledProperty]) -> bool {
+    properties
+        .iter()
+        .any(|property| property.name == "solid" && property.value == true)
+}
+
+#[derive(Default)]
+struct TiledMapLoader;
+
+#[derive(Debug, Error)]
+enum TiledMapLoaderError {
+    #[error("could not read map: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse map: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("could not read tileset: {0}")]
+    ReadTileset(#[from] ReadAssetBytesError),
+    #[error("invalid path in map: {0}")]
+    Path(#[from] ParseAssetPathError),
+    #[error("{0} aren't supported")]
+    Unsupported(&'static str),
+    #[error("tiles are {0}x{1}, but only square tiles are supported")]
+    NonSquareTiles(u32, u32),
+    #[error("layer \"{0}\" doesn't match the size of the map")]
+    LayerSize(String),
+    #[error("tile {0} isn't in any tileset")]
+    UnknownTile(u32),
+    #[error("map has no \"{}\" spawn point", TileMap::PLAYER_SPAWN)]
+    NoPlayerSpawn,
+    #[error("player starts at {0}, which isn't on a free tile")]
+    PlayerStart(IVec2),
+}
+
+/// A tileset ready to hand out [`TileKind`]s.
+struct LoadedTileset {
+    firstgid: u32,
+    tilecount: u32,
+    image: Handle<Image>,
+    layout: Handle<TextureAtlasLayout>,
+    solid_tiles: Vec<u32>,
+}
+
+impl AssetLoader for TiledMapLoader {
+    type Asset = TileMap;
+    type Settings = ();
+    type Error = TiledMapLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let map: TiledMap = serde_json::from_slice(&bytes)?;
+        if map.orientation != "orthogonal" {
+            return Err(TiledMapLoaderError::Unsupported("non-orthogonal maps"));
+        }
+        if map.infinite {
+            return Err(TiledMapLoaderError::Unsupported("infinite maps"));
+        }
+        if map.tilewidth != map.tileheight {
+            return Err(TiledMapLoaderError::NonSquareTiles(
+                map.tilewidth,
+                map.tileheight,
+            ));
+        }
+
+        let mut tilesets = Vec::with_capacity(map.tilesets.len());
+        for (index, tileset_ref) in map.tilesets.into_iter().enumerate() {
+            tilesets.push(load_tileset(load_context, index, tileset_ref).await?);
+        }
+        // looking tiles up goes through the tilesets from the last one down
+        tilesets.sort_by_key(|tileset| tileset.firstgid);
+
+        let size = UVec2::new(map.width, map.height);
+        let mut builder = TileMapBuilder {
+            size,
+            tile_size: map.tilewidth as f32,
+            tilesets,
+            tile_kinds: Vec::new(),
+            kinds_by_gid: HashMap::new(),
+            layers: Vec::new(),
+            objects: Vec::new(),
+            spawn_points: HashMap::new(),
+        };
+        for layer in map.layers {
+            builder.add_layer(layer)?;
+        }
+
+        let player_start = *builder
+            .spawn_points
+            .get(TileMap::PLAYER_SPAWN)
+            .ok_or(TiledMapLoaderError::NoPlayerSpawn)?;
+        let map = TileMap {
+            size,
+            tile_size: builder.tile_size,
+            tile_kinds: builder.tile_kinds,
+            layers: builder.layers,
+            objects: builder.objects,
+            spawn_points: builder.spawn_points,
+            properties: map_properties(map.properties),
+        };
+        if TileGrid::new(&map).is_solid(player_start) {
+            return Err(TiledMapLoaderError::PlayerStart(player_start));
+        }
+        Ok(map)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tmj"]
+    }
+}
+
+async fn load_tileset(
+    load_context: &mut LoadContext<'_>,
+    index: usize,
+    tileset_ref: TiledTilesetRef,
+) -> Result<LoadedTileset, TiledMapLoaderError> {
+    // images are relative to the file the tileset is in
+    let (tileset, tileset_path) = match tileset_ref.source {
+        Some(source) => {
+            if !source.ends_with(".tsj") && !source.ends_with(".json") {
+                return Err(TiledMapLoaderError::Unsupported(
+                    "tilesets not saved as JSON",
+                ));
+            }
+            let path = load_context.asset_path().resolve_embed(&source)?;
+            let bytes = load_context.read_asset_bytes(path.clone()).await?;
+            (serde_json::from_slice(&bytes)?, path)
+        }
+        None => (
+            tileset_ref
+                .tileset
+                .ok_or(TiledMapLoaderError::Unsupported("tilesets with no tiles"))?,
+            load_context.asset_path().clone(),
+        ),
+    };
+    let TiledTileset {
+        tilewidth,
+        tileheight,
+        tilecount,
+        columns,
+        image,
+        margin,
+        spacing,
+        tiles,
+    } = tileset;
+    let image = image.ok_or(TiledMapLoaderError::Unsupported(
+        "image collection tilesets",
+    ))?;
+
+    let image: AssetPath = tileset_path.resolve_embed(&image)?;
+    let image = load_context
+        .loader()
+        // keep pixel art crisp
+        .with_settings(|settings: &mut ImageLoaderSettings| {
+            settings.sampler = ImageSampler::nearest();
+        })
+        .load(image);
+    let layout = TextureAtlasLayout::from_grid(
+        UVec2::new(tilewidth, tileheight),
+        columns,
+        tilecount.div_ceil(columns.max(1)),
+        Some(UVec2::splat(spacing)),
+        Some(UVec2::splat(margin)),
+    );
+    let layout = load_context.add_labeled_asset(format!("tileset{index}"), layout);
+    Ok(LoadedTileset {
+        firstgid: tileset_ref.firstgid,
+        tilecount,
+        image,
+        layout,
+        solid_tiles: tiles
+            .iter()
+            .filter(|tile| is_solid(&tile.properties))
+            .map(|tile| tile.id)
+            .collect(),
+    })
+}
+
+struct TileMapBuilder {
+    size: UVec2,
+    tile_size: f32,
+    tilesets: Vec<LoadedTileset>,
+    tile_kinds: Vec<TileKind>,
+    /// Flipped tiles get their own kind.
+    kinds_by_gid: HashMap<u32, usize>,
+    layers: Vec<TileLayer>,
+    objects: Vec<MapObject>,
+    spawn_points: HashMap<String, IVec2>,
+}
+
+impl TileMapBuilder {
+    fn add_layer(&mut self, layer: TiledLayer) -> Result<(), TiledMapLoaderError> {
+        match layer {
+            TiledLayer::TileLayer {
+                name,
+                width,
+                height,
+                data,
+                visible,
+                properties,
+            } => {
+                let Ok(data) = serde_json::from_value::<Vec<u32>>(data) else {
+                    return Err(TiledMapLoaderError::Unsupported(
+                        "compressed or encoded tile layers",
+                    ));
+                };
+                if UVec2::new(width, height) != self.size || data.len() != (width * height) as usize
+                {
+                    return Err(TiledMapLoaderError::LayerSize(name));
+                }
+                let tiles = data
+                    .into_iter()
+                    .map(|gid| match gid {
+                        0 => Ok(None),
+                        _ => self.tile_kind(gid).map(Some),
+                    })
+                    .collect::<Result<_, _>>()?;
+                self.layers.push(TileLayer {
+                    tiles,
+                    visible,
+                    solid: is_solid(&properties),
+                });
+            }
+            TiledLayer::ObjectGroup { objects } => {
+                for object in objects {
+                    self.add_object(object);
+                }
+            }
+            TiledLayer::Group { layers } => {
+                for layer in layers {
+                    self.add_layer(layer)?;
+                }
+            }
+            TiledLayer::ImageLayer {} => {}
+        }
+        Ok(())
+    }
+
+    fn tile_kind(&mut self, gid: u32) -> Result<usize, TiledMapLoaderError> {
+        if let Some(&kind) = self.kinds_by_gid.get(&gid) {
+            return Ok(kind);
+        }
+        let tile = gid & !FLIP_FLAGS;
+        let tileset = self
+            .tilesets
+            .iter()
+            .rev()
+            .find(|tileset| tileset.firstgid <= tile)
+            .filter(|tileset| tile - tileset.firstgid < tileset.tilecount)
+            .ok_or(TiledMapLoaderError::UnknownTile(tile))?;
+        let id = tile - tileset.firstgid;
+        self.tile_kinds.push(TileKind {
+            color: Color::WHITE,
+            sprite: Some(TileSprite {
+                image: tileset.image.clone(),
+                layout: tileset.layout.clone(),
+                index: id as usize,
+                flip_x: gid & FLIPPED_HORIZONTALLY != 0,
+                flip_y: gid & FLIPPED_VERTICALLY != 0,
+            }),
+            solid: tileset.solid_tiles.contains(&id),
+        });
+        let kind = self.tile_kinds.len() - 1;
+        self.kinds_by_gid.insert(gid, kind);
+        Ok(kind)
+    }
+
+    fn add_object(&mut self, object: TiledObject) {
+        let tile_size = self.tile_size;
+        let top = if object.gid.is_some() {
+            object.y - object.height
+        } else {
+            object.y
+        };
+        let min = Vec2::new(object.x, top) / tile_size;
+        let max = Vec2::new(object.x + object.width, top + object.height) / tile_size;
+        let position = min.floor().as_ivec2();
+        // points and thin objects still cover the tile they're on
+        let size = (max.ceil().as_ivec2() - position)
+            .max(IVec2::ONE)
+            .as_uvec2();
+
+        let kind = if object.kind.is_empty() {
+            &object.class
+        } else {
+            &object.kind
+        };
+        let kind = match kind.to_ascii_lowercase().as_str() {
+            "spawn" => {
+                self.spawn_points.insert(object.name, position);
+                return;
+            }
+            "npc" => MapObjectKind::Npc,
+            "trigger" => MapObjectKind::Trigger,
+            "collision" => MapObjectKind::Collision,
+            _ => {
+                warn!(
+                    "Ignoring map object \"{}\" with unknown type \"{kind}\"",
+                    object.name
+                );
+                return;
+            }
+        };
+        self.objects.push(MapObject {
+            kind,
+            name: object.name,
+            position,
+            size,
+            properties: map_properties(object.properties),
+        });
+    }
+}
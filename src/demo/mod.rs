@@ -1,7 +1,23 @@
 use bevy::prelude::*;
 
+mod camera;
+pub mod conversation;
+pub mod cutscene;
 pub mod level;
+pub mod map;
+pub mod movement;
+pub mod npc;
+pub mod player;
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_plugins((level::plugin,));
+    app.add_plugins((
+        camera::plugin,
+        conversation::plugin,
+        cutscene::plugin,
+        level::plugin,
+        map::plugin,
+        movement::plugin,
+        npc::plugin,
+        player::plugin,
+    ));
 }
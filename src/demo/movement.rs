@@ -0,0 +1,134 @@
+//! Moving things around a [`TileGrid`] one tile at a time.
+
+use bevy::prelude::*;
+
+use crate::{AppSystems, PausableSystems, demo::map::TileGrid};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<GridMover>();
+    app.register_type::<FacingMarker>();
+
+    app.add_systems(
+        Update,
+        (move_on_grid, show_facing)
+            .chain()
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems),
+    );
+}
+
+/// Where anything walking around the map is drawn.
+pub const ACTOR_Z: f32 = 0.5;
+
+/// Steps its entity from tile to tile in the direction of [`Self::intent`], unless the next tile
+/// is solid or another [`GridMover`] is on it.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct GridMover {
+    /// The tile the entity is on, or moving onto.
+    pub position: IVec2,
+    /// The direction to step in next, set by whatever controls the entity.
+    pub intent: Option<IVec2>,
+    /// The direction last stepped in, or tried to.
+    pub facing: IVec2,
+    /// Tiles per second.
+    pub speed: f32,
+    /// The tile the current step started from, and how far along the step is (0 to 1).
+    step: Option<(IVec2, f32)>,
+}
+
+impl GridMover {
+    pub fn new(position: IVec2, speed: f32) -> Self {
+        Self {
+            position,
+            intent: None,
+            // rows count downwards, so this faces the camera
+            facing: IVec2::Y,
+            speed,
+            step: None,
+        }
+    }
+
+    pub fn is_moving(&self) -> bool {
+        self.step.is_some()
+    }
+
+    /// The tile in front of the entity.
+    pub fn facing_tile(&self) -> IVec2 {
+        self.position + self.facing
+    }
+}
+
+/// Sits on the side of its parent [`GridMover`] it's facing.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct FacingMarker {
+    /// How far from the parent's center.
+    pub distance: f32,
+}
+
+const FACING_MARKER_COLOR: Color = Color::srgb(0.1, 0.1, 0.15);
+
+pub fn facing_marker(size: f32, distance: f32) -> impl Bundle {
+    (
+        Name::new("Facing Marker"),
+        FacingMarker { distance },
+        Sprite::from_color(FACING_MARKER_COLOR, Vec2::splat(size)),
+        Transform::default(),
+    )
+}
+
+pub(super) fn move_on_grid(
+    grid_query: Query<&TileGrid>,
+    mut mover_query: Query<(&mut GridMover, &mut Transform)>,
+    time: Res<Time>,
+) {
+    let Ok(grid) = grid_query.single() else {
+        return;
+    };
+    let mut occupied: Vec<IVec2> = mover_query
+        .iter()
+        .map(|(mover, _)| mover.position)
+        .collect();
+    for (mut mover, mut transform) in &mut mover_query {
+        if mover.step.is_none()
+            && let Some(direction) = mover.intent
+        {
+            mover.facing = direction;
+            let target = mover.position + direction;
+            if !grid.is_solid(target) && !occupied.contains(&target) {
+                occupied.push(target);
+                mover.step = Some((mover.position, 0.));
+                mover.position = target;
+            }
+        }
+        let Some((from, progress)) = mover.step else {
+            continue;
+        };
+
+        let progress = progress + time.delta_secs() * mover.speed;
+        let translation = if progress >= 1. {
+            mover.step = None;
+            grid.tile_to_world(mover.position)
+        } else {
+            mover.step = Some((from, progress));
+            grid.tile_to_world(from)
+                .lerp(grid.tile_to_world(mover.position), progress)
+        };
+        transform.translation = translation.extend(transform.translation.z);
+    }
+}
+
+fn show_facing(
+    mover_query: Query<(&GridMover, &Children), Changed<GridMover>>,
+    mut marker_query: Query<(&FacingMarker, &mut Transform)>,
+) {
+    for (mover, children) in &mover_query {
+        let mut markers = marker_query.iter_many_mut(children);
+        while let Some((marker, mut transform)) = markers.fetch_next() {
+            let offset = Vec2::new(mover.facing.x as f32, -mover.facing.y as f32) * marker.distance;
+            // just above the parent's sprite
+            transform.translation = offset.extend(0.01);
+        }
+    }
+}
@@ -0,0 +1,88 @@
+//! Characters standing around the overworld, who can be talked to.
+
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems, PausableSystems,
+    demo::{
+        conversation::StartConversation,
+        cutscene::cutscene_playing,
+        map::{MapObject, TileGrid},
+        movement::{ACTOR_Z, GridMover, facing_marker},
+        player::{Player, record_player_input},
+    },
+    input::{Action, action_just_pressed},
+    text_boxes::TextBox,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Npc>();
+
+    app.add_systems(
+        Update,
+        talk_to_npcs
+            .run_if(action_just_pressed(Action::Confirm).and(not(cutscene_playing)))
+            // so the player doesn't take a step as the conversation starts
+            .after(record_player_input)
+            .in_set(AppSystems::RecordInput)
+            .in_set(PausableSystems),
+    );
+}
+
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct Npc {
+    /// The conversation from the dialogue script to start when the player talks to them.
+    pub dialogue: Option<String>,
+}
+
+/// Used when the map doesn't give the NPC a `color`.
+const NPC_COLOR: Color = Color::srgb(0.85, 0.45, 0.9);
+/// Tiles per second.
+const NPC_SPEED: f32 = 4.;
+
+/// An NPC placed on the map, who says the conversation named by its `dialogue` property. Its
+/// properties are kept on it for whatever else needs them.
+pub fn npc(object: &MapObject, grid: &TileGrid) -> impl Bundle {
+    let color = object.properties.get_color("color").unwrap_or(NPC_COLOR);
+    let size = grid.tile_size * 0.7;
+    (
+        Name::new(object.name.clone()),
+        Npc {
+            dialogue: object.properties.get_str("dialogue").map(str::to_string),
+        },
+        GridMover::new(object.position, NPC_SPEED),
+        Sprite::from_color(color, Vec2::splat(size)),
+        Transform::from_translation(grid.tile_to_world(object.position).extend(ACTOR_Z)),
+        object.properties.clone(),
+        children![facing_marker(size * 0.25, size * 0.35)],
+    )
+}
+
+/// Starts the conversation of the NPC the player is facing. The player stays put until the text
+/// box closes.
+fn talk_to_npcs(
+    mut player: Single<&mut GridMover, With<Player>>,
+    mut npc_query: Query<(&Npc, &mut GridMover, &Name), Without<Player>>,
+    text_box_query: Query<(), With<TextBox>>,
+    mut conversation_events: EventWriter<StartConversation>,
+) {
+    if player.is_moving() || !text_box_query.is_empty() {
+        return;
+    }
+    let facing_tile = player.facing_tile();
+    let Some((npc, mut npc_mover, name)) = npc_query
+        .iter_mut()
+        .find(|(_, mover, _)| mover.position == facing_tile)
+    else {
+        return;
+    };
+    let Some(dialogue) = &npc.dialogue else {
+        debug!("{name} has nothing to say");
+        return;
+    };
+
+    npc_mover.facing = -player.facing;
+    player.intent = None;
+    conversation_events.write(StartConversation::new(dialogue.clone()));
+}
@@ -0,0 +1,90 @@
+//! The player character.
+
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems, PausableSystems,
+    demo::{
+        cutscene::CutsceneRunner,
+        map::TileGrid,
+        movement::{ACTOR_Z, GridMover, facing_marker},
+    },
+    input::{Action, ActionInput},
+    text_boxes::TextBox,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Player>();
+
+    app.add_systems(
+        Update,
+        record_player_input
+            .in_set(AppSystems::RecordInput)
+            .in_set(PausableSystems),
+    );
+}
+
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct Player;
+
+const PLAYER_COLOR: Color = Color::srgb(0.95, 0.9, 0.3);
+/// Tiles per second.
+const PLAYER_SPEED: f32 = 6.;
+
+/// The player, standing on the tile at `position`.
+pub fn player(position: IVec2, grid: &TileGrid) -> impl Bundle {
+    let size = grid.tile_size * 0.7;
+    (
+        Name::new("Player"),
+        Player,
+        GridMover::new(position, PLAYER_SPEED),
+        Sprite::from_color(PLAYER_COLOR, Vec2::splat(size)),
+        Transform::from_translation(grid.tile_to_world(position).extend(ACTOR_Z)),
+        children![facing_marker(size * 0.25, size * 0.35)],
+    )
+}
+
+/// How far the left stick has to be pushed to walk.
+const STICK_DEADZONE: f32 = 0.5;
+
+pub(super) fn record_player_input(
+    input: ActionInput,
+    text_box_query: Query<(), With<TextBox>>,
+    cutscene_query: Query<(), With<CutsceneRunner>>,
+    mut mover_query: Query<&mut GridMover, With<Player>>,
+) {
+    let mut intent = if input.pressed(Action::MoveUp) {
+        Some(IVec2::NEG_Y)
+    } else if input.pressed(Action::MoveDown) {
+        Some(IVec2::Y)
+    } else if input.pressed(Action::MoveLeft) {
+        Some(IVec2::NEG_X)
+    } else if input.pressed(Action::MoveRight) {
+        Some(IVec2::X)
+    } else {
+        None
+    };
+    if intent.is_none() {
+        // go with whichever way the stick is pushed furthest, rows count downwards
+        intent = input
+            .gamepads()
+            .map(|gamepad| gamepad.left_stick())
+            .find(|stick| stick.max_element().max(-stick.min_element()) >= STICK_DEADZONE)
+            .map(|stick| {
+                if stick.x.abs() > stick.y.abs() {
+                    IVec2::new(stick.x.signum() as i32, 0)
+                } else {
+                    IVec2::new(0, -stick.y.signum() as i32)
+                }
+            });
+    }
+    // hold still while someone's talking, or a cutscene is moving the player around
+    if !text_box_query.is_empty() || !cutscene_query.is_empty() {
+        intent = None;
+    }
+
+    for mut mover in &mut mover_query {
+        mover.intent = intent;
+    }
+}
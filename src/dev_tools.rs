@@ -1,11 +1,12 @@
 //! Development tools for the game. This plugin is only enabled in dev builds.
 
-use bevy::{
-    dev_tools::states::log_transitions, input::common_conditions::input_just_pressed, prelude::*,
-    ui::UiDebugOptions,
-};
+use bevy::{dev_tools::states::log_transitions, prelude::*, ui::UiDebugOptions};
 
-use crate::screens::Screen;
+use crate::{
+    game_flags::{GameFlagChanged, GameFlags},
+    input::{Action, action_just_pressed},
+    screens::Screen,
+};
 
 pub(super) fn plugin(app: &mut App) {
     // Log `Screen` state transitions.
@@ -14,12 +15,81 @@ pub(super) fn plugin(app: &mut App) {
     // Toggle the debug overlay for UI.
     app.add_systems(
         Update,
-        toggle_debug_ui.run_if(input_just_pressed(TOGGLE_KEY)),
+        toggle_debug_ui.run_if(action_just_pressed(Action::DebugUi)),
     );
-}
 
-const TOGGLE_KEY: KeyCode = KeyCode::Backquote;
+    // Log game flag changes, and toggle an overlay listing all of them.
+    app.add_systems(
+        Update,
+        (
+            log_game_flag_changes,
+            toggle_game_flags_overlay.run_if(action_just_pressed(Action::DebugFlags)),
+            update_game_flags_overlay.run_if(resource_changed::<GameFlags>),
+        ),
+    );
+}
 
 fn toggle_debug_ui(mut options: ResMut<UiDebugOptions>) {
     options.toggle();
 }
+
+fn log_game_flag_changes(mut changed_events: EventReader<GameFlagChanged>) {
+    for event in changed_events.read() {
+        match &event.value {
+            Some(value) => info!("Game flag \"{}\" set to {value}", event.flag),
+            None => info!("Game flag \"{}\" removed", event.flag),
+        }
+    }
+}
+
+#[derive(Component)]
+struct GameFlagsOverlay;
+
+fn toggle_game_flags_overlay(
+    mut commands: Commands,
+    overlay_query: Query<Entity, With<GameFlagsOverlay>>,
+    flags: Res<GameFlags>,
+) {
+    if let Ok(overlay) = overlay_query.single() {
+        commands.entity(overlay).despawn();
+        return;
+    }
+    commands.spawn((
+        Name::new("Game Flags Overlay"),
+        GameFlagsOverlay,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.),
+            left: Val::Px(8.),
+            padding: UiRect::all(Val::Px(8.)),
+            ..default()
+        },
+        BackgroundColor(Color::BLACK.with_alpha(0.7)),
+        GlobalZIndex(10),
+        Pickable::IGNORE,
+        Text(game_flags_text(&flags)),
+        TextFont::from_font_size(16.),
+    ));
+}
+
+fn update_game_flags_overlay(
+    mut overlay_query: Query<&mut Text, With<GameFlagsOverlay>>,
+    flags: Res<GameFlags>,
+) {
+    for mut text in &mut overlay_query {
+        text.0 = game_flags_text(&flags);
+    }
+}
+
+fn game_flags_text(flags: &GameFlags) -> String {
+    let mut lines: Vec<_> = flags
+        .0
+        .iter()
+        .map(|(flag, value)| format!("{flag} = {value}"))
+        .collect();
+    if lines.is_empty() {
+        return "No game flags set".to_string();
+    }
+    lines.sort();
+    lines.join("\n")
+}
@@ -0,0 +1,259 @@
+//! Dialogue scripts: named conversations authored as `.dialogue.ron` assets, so writers can
+//! edit dialogue without recompiling.
+
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    asset_tracking::LoadResource,
+    game_flags::{FlagCondition, FlagValue, GameFlags},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<DialogueScript>();
+    app.init_asset_loader::<DialogueScriptLoader>();
+
+    app.register_type::<DialogueAssets>();
+    app.load_resource::<DialogueAssets>();
+}
+
+/// A collection of named conversations loaded from a `.dialogue.ron` file, along with the
+/// speakers that appear in them.
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct DialogueScript {
+    #[serde(default)]
+    pub speakers: HashMap<String, Speaker>,
+    pub conversations: HashMap<String, Conversation>,
+}
+
+impl DialogueScript {
+    pub fn conversation(&self, id: &str) -> Option<&Conversation> {
+        self.conversations.get(id)
+    }
+
+    /// The conversation with only the lines and choices whose conditions hold, or `None` if it
+    /// doesn't exist or none of its lines hold.
+    pub fn conversation_for(&self, id: &str, flags: &GameFlags) -> Option<Conversation> {
+        let conversation = self.conversation(id)?;
+        let holds = |condition: &Option<FlagCondition>| {
+            condition
+                .as_ref()
+                .is_none_or(|condition| condition.holds(flags))
+        };
+        let lines: Vec<_> = conversation
+            .lines
+            .iter()
+            .filter(|line| holds(&line.condition))
+            .cloned()
+            .collect();
+        if lines.is_empty() {
+            return None;
+        }
+        Some(Conversation {
+            lines,
+            choices: conversation
+                .choices
+                .iter()
+                .filter(|choice| holds(&choice.condition))
+                .cloned()
+                .collect(),
+        })
+    }
+
+    /// Catch authoring mistakes at load time rather than in the middle of a conversation.
+    fn validate(&self) -> Result<(), DialogueScriptLoaderError> {
+        for (id, conversation) in &self.conversations {
+            // Lines can all have conditions, as long as one of them holds whenever the
+            // conversation comes up. It's skipped with a warning otherwise.
+            if conversation.lines.is_empty() {
+                return Err(DialogueScriptLoaderError::NoLines(id.clone()));
+            }
+            let choice_count = conversation.choices.len();
+            if choice_count != 0 && !(MIN_CHOICES..=MAX_CHOICES).contains(&choice_count) {
+                return Err(DialogueScriptLoaderError::ChoiceCount(
+                    id.clone(),
+                    choice_count,
+                ));
+            }
+            for choice in &conversation.choices {
+                if let Some(next) = &choice.next
+                    && !self.conversations.contains_key(next)
+                {
+                    return Err(DialogueScriptLoaderError::UnknownConversation(
+                        choice.id.clone(),
+                        next.clone(),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A sequence of lines shown one after another in a text box, optionally ending with a set of
+/// choices for the player.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Conversation {
+    pub lines: Vec<DialogueLine>,
+    #[serde(default)]
+    pub choices: Vec<DialogueChoice>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Reflect)]
+pub struct DialogueLine {
+    /// Who is saying the line, if anyone (narration has no speaker).
+    #[serde(default)]
+    pub speaker: Option<String>,
+    pub text: String,
+    /// Path to the speaker's portrait image, relative to the assets folder.
+    #[serde(default)]
+    pub portrait: Option<String>,
+    /// The loaded [`Self::portrait`], filled in by the loader.
+    #[serde(skip)]
+    pub portrait_image: Option<Handle<Image>>,
+    /// The [`Speaker::voice`] of [`Self::speaker`], filled in by the loader.
+    #[serde(skip)]
+    pub voice: Option<DialogueVoice>,
+    /// The line is skipped unless this holds, written as `if: "flag"`.
+    #[serde(default, rename = "if")]
+    pub condition: Option<FlagCondition>,
+}
+
+/// Details about someone who speaks in a [`DialogueScript`], keyed by the name used for
+/// [`DialogueLine::speaker`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct Speaker {
+    #[serde(default)]
+    pub voice: Option<DialogueVoice>,
+}
+
+/// The "blip" played as a speaker's lines are typed out.
+#[derive(Debug, Clone, Deserialize, Reflect)]
+pub struct DialogueVoice {
+    /// Path to the blip sound, relative to the assets folder.
+    pub sound: String,
+    /// The loaded [`Self::sound`], filled in by the loader.
+    #[serde(skip)]
+    pub sound_handle: Handle<AudioSource>,
+    /// Playback speed of the blip, which also raises or lowers its pitch.
+    #[serde(default = "default_pitch")]
+    pub pitch: f32,
+    /// Each blip's pitch is shifted by a random amount of up to this much either way.
+    #[serde(default)]
+    pub pitch_variation: f32,
+}
+
+fn default_pitch() -> f32 {
+    1.
+}
+
+/// An option presented to the player after the last line of a [`Conversation`].
+#[derive(Debug, Clone, Deserialize, Reflect)]
+pub struct DialogueChoice {
+    /// Identifies the choice in [`TextBoxChoiceMade`](crate::text_boxes::TextBoxChoiceMade).
+    pub id: String,
+    pub text: String,
+    /// The conversation to jump to when this is picked. The dialogue ends if there isn't one, or
+    /// none of its lines' conditions hold.
+    #[serde(default)]
+    pub next: Option<String>,
+    /// The choice is left out unless this holds, written as `if: "flag"`.
+    #[serde(default, rename = "if")]
+    pub condition: Option<FlagCondition>,
+    /// [`GameFlags`] to set when this is picked.
+    #[serde(default)]
+    pub set: HashMap<String, FlagValue>,
+}
+
+pub const MIN_CHOICES: usize = 2;
+pub const MAX_CHOICES: usize = 4;
+
+#[derive(Default)]
+struct DialogueScriptLoader;
+
+#[derive(Debug, Error)]
+enum DialogueScriptLoaderError {
+    #[error("could not read dialogue script: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse dialogue script: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error(
+        "conversation \"{0}\" has {1} choices, expected between {MIN_CHOICES} and {MAX_CHOICES}"
+    )]
+    ChoiceCount(String, usize),
+    #[error("conversation \"{0}\" has no lines")]
+    NoLines(String),
+    #[error("choice \"{0}\" leads to unknown conversation \"{1}\"")]
+    UnknownConversation(String, String),
+}
+
+impl AssetLoader for DialogueScriptLoader {
+    type Asset = DialogueScript;
+    type Settings = ();
+    type Error = DialogueScriptLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let mut script: DialogueScript = ron::de::from_bytes(&bytes)?;
+        script.validate()?;
+        // Portraits and voices become dependencies of the script, so they're ready as soon as it
+        // is.
+        for voice in script
+            .speakers
+            .values_mut()
+            .filter_map(|speaker| speaker.voice.as_mut())
+        {
+            voice.sound_handle = load_context.load(voice.sound.clone());
+        }
+        for line in script
+            .conversations
+            .values_mut()
+            .flat_map(|conversation| conversation.lines.iter_mut())
+        {
+            line.portrait_image = line
+                .portrait
+                .as_ref()
+                .map(|path| load_context.load(path.clone()));
+            line.voice = line
+                .speaker
+                .as_ref()
+                .and_then(|speaker| script.speakers.get(speaker))
+                .and_then(|speaker| speaker.voice.clone());
+        }
+        Ok(script)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["dialogue.ron"]
+    }
+}
+
+/// The game's dialogue scripts. Handles stay the same across hot reloads, so look the script
+/// up in [`Assets<DialogueScript>`] whenever it's needed rather than caching its contents.
+#[derive(Resource, Asset, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct DialogueAssets {
+    #[dependency]
+    pub script: Handle<DialogueScript>,
+}
+
+impl FromWorld for DialogueAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            script: assets.load("dialogue/demo.dialogue.ron"),
+        }
+    }
+}
@@ -0,0 +1,348 @@
+//! Named values that make up the story so far, for dialogue and cutscenes to check and change.
+
+use std::{cmp::Ordering, collections::HashMap, fmt};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::screens::Screen;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<GameFlags>();
+    app.init_resource::<GameFlags>();
+    app.add_event::<GameFlagChanged>();
+
+    app.add_systems(OnEnter(Screen::Gameplay), reset_game_flags);
+    app.add_systems(
+        PostUpdate,
+        report_game_flag_changes.run_if(resource_changed::<GameFlags>),
+    );
+}
+
+/// The game's flags and variables, cleared when a new game starts. A flag that was never set
+/// counts as `false`.
+#[derive(Resource, Reflect, Debug, Clone, Default)]
+#[reflect(Resource)]
+pub struct GameFlags(pub HashMap<String, FlagValue>);
+
+impl GameFlags {
+    pub fn get(&self, name: &str) -> Option<&FlagValue> {
+        self.0.get(name)
+    }
+
+    /// Whether the flag is set to something other than `false`, `0` or an empty string.
+    pub fn is_set(&self, name: &str) -> bool {
+        self.get(name).is_some_and(FlagValue::is_truthy)
+    }
+
+    pub fn set(&mut self, name: impl Into<String>, value: impl Into<FlagValue>) {
+        self.0.insert(name.into(), value.into());
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.0.remove(name);
+    }
+}
+
+/// A value in [`GameFlags`]. Written in assets as a plain `true`, `3` or `"text"`.
+#[derive(Reflect, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FlagValue {
+    Bool(bool),
+    Int(i64),
+    String(String),
+}
+
+impl FlagValue {
+    fn is_truthy(&self) -> bool {
+        match self {
+            Self::Bool(value) => *value,
+            Self::Int(value) => *value != 0,
+            Self::String(value) => !value.is_empty(),
+        }
+    }
+
+    /// Reads a value written in a [`FlagCondition`]: `true`, `false`, a number, or text with or
+    /// without quotes.
+    fn parse(value: &str) -> Self {
+        let value = value.trim();
+        if let Ok(value) = value.parse() {
+            Self::Bool(value)
+        } else if let Ok(value) = value.parse() {
+            Self::Int(value)
+        } else {
+            Self::String(value.trim_matches('"').to_string())
+        }
+    }
+}
+
+impl fmt::Display for FlagValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Bool(value) => value.fmt(f),
+            Self::Int(value) => value.fmt(f),
+            Self::String(value) => write!(f, "\"{value}\""),
+        }
+    }
+}
+
+impl From<bool> for FlagValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<i64> for FlagValue {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<String> for FlagValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+/// A check against [`GameFlags`], written in assets as `"flag"`, `"!flag"`, or a comparison
+/// like `"gold >= 10"` or `"ending == good"`. Ordering comparisons only hold between numbers.
+#[derive(Reflect, Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct FlagCondition {
+    pub flag: String,
+    pub test: FlagTest,
+}
+
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlagComparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(Reflect, Debug, Clone, PartialEq)]
+pub enum FlagTest {
+    IsSet,
+    IsNotSet,
+    Compare(FlagComparison, FlagValue),
+}
+
+impl FlagCondition {
+    pub fn holds(&self, flags: &GameFlags) -> bool {
+        match &self.test {
+            FlagTest::IsSet => flags.is_set(&self.flag),
+            FlagTest::IsNotSet => !flags.is_set(&self.flag),
+            FlagTest::Compare(comparison, expected) => {
+                let value = flags.get(&self.flag);
+                // unset flags are equal to `false`, `0` and `""`
+                let equal = match value {
+                    Some(value) => value == expected,
+                    None => !expected.is_truthy(),
+                };
+                let ordering = match (value, expected) {
+                    (None, FlagValue::Int(expected)) => Some(0.cmp(expected)),
+                    (Some(FlagValue::Int(value)), FlagValue::Int(expected)) => {
+                        Some(value.cmp(expected))
+                    }
+                    _ => None,
+                };
+                match comparison {
+                    FlagComparison::Equal => equal,
+                    FlagComparison::NotEqual => !equal,
+                    FlagComparison::Less => ordering.is_some_and(Ordering::is_lt),
+                    FlagComparison::LessOrEqual => ordering.is_some_and(Ordering::is_le),
+                    FlagComparison::Greater => ordering.is_some_and(Ordering::is_gt),
+                    FlagComparison::GreaterOrEqual => ordering.is_some_and(Ordering::is_ge),
+                }
+            }
+        }
+    }
+}
+
+impl TryFrom<String> for FlagCondition {
+    type Error = String;
+
+    fn try_from(condition: String) -> Result<Self, Self::Error> {
+        // longer operators first, so `>=` isn't read as `>`
+        const OPERATORS: [(&str, FlagComparison); 6] = [
+            ("==", FlagComparison::Equal),
+            ("!=", FlagComparison::NotEqual),
+            ("<=", FlagComparison::LessOrEqual),
+            (">=", FlagComparison::GreaterOrEqual),
+            ("<", FlagComparison::Less),
+            (">", FlagComparison::Greater),
+        ];
+        let condition = condition.trim();
+        let (flag, test) = match OPERATORS
+            .iter()
+            .find_map(|(operator, comparison)| Some((condition.split_once(operator)?, comparison)))
+        {
+            Some(((flag, value), comparison)) => (
+                flag.trim(),
+                FlagTest::Compare(*comparison, FlagValue::parse(value)),
+            ),
+            None => match condition.strip_prefix('!') {
+                Some(flag) => (flag.trim(), FlagTest::IsNotSet),
+                None => (condition, FlagTest::IsSet),
+            },
+        };
+        if flag.is_empty() || flag.contains(char::is_whitespace) {
+            return Err(format!("invalid flag condition \"{condition}\""));
+        }
+        Ok(Self {
+            flag: flag.to_string(),
+            test,
+        })
+    }
+}
+
+/// Sent whenever a flag is set, changed or removed.
+#[derive(Event, Debug, Clone)]
+pub struct GameFlagChanged {
+    pub flag: String,
+    /// `None` if the flag was removed.
+    pub value: Option<FlagValue>,
+}
+
+pub fn reset_game_flags(mut flags: ResMut<GameFlags>) {
+    flags.0.clear();
+}
+
+/// Compares the flags with how they were the last time they changed, so edits made from
+/// anywhere are reported.
+fn report_game_flag_changes(
+    flags: Res<GameFlags>,
+    mut previous: Local<HashMap<String, FlagValue>>,
+    mut changed_events: EventWriter<GameFlagChanged>,
+) {
+    for (flag, value) in &flags.0 {
+        if previous.get(flag) != Some(value) {
+            changed_events.write(GameFlagChanged {
+                flag: flag.clone(),
+                value: Some(value.clone()),
+            });
+        }
+    }
+    for flag in previous.keys() {
+        if !flags.0.contains_key(flag) {
+            changed_events.write(GameFlagChanged {
+                flag: flag.clone(),
+                value: None,
+            });
+        }
+    }
+    *previous = flags.0.clone();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn condition(source: &str) -> FlagCondition {
+        FlagCondition::try_from(source.to_string()).unwrap()
+    }
+
+    fn holds(source: &str, flags: &GameFlags) -> bool {
+        condition(source).holds(flags)
+    }
+
+    fn flags() -> GameFlags {
+        let mut flags = GameFlags::default();
+        flags.set("met_guard", true);
+        flags.set("gold", 3);
+        flags.set("ending", "good".to_string());
+        flags.set("zero", 0);
+        flags
+    }
+
+    #[test]
+    fn parses_each_form() {
+        assert_eq!(condition("met_guard").test, FlagTest::IsSet);
+        assert_eq!(condition("!met_guard").test, FlagTest::IsNotSet);
+        assert_eq!(condition(" ! met_guard ").flag, "met_guard");
+        assert_eq!(
+            condition("gold >= 3").test,
+            FlagTest::Compare(FlagComparison::GreaterOrEqual, FlagValue::Int(3))
+        );
+        assert_eq!(
+            condition("gold<=3").test,
+            FlagTest::Compare(FlagComparison::LessOrEqual, FlagValue::Int(3))
+        );
+        assert_eq!(
+            condition("ending == \"good\"").test,
+            FlagTest::Compare(FlagComparison::Equal, FlagValue::String("good".to_string()))
+        );
+        assert_eq!(
+            condition("met_guard != false").test,
+            FlagTest::Compare(FlagComparison::NotEqual, FlagValue::Bool(false))
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_conditions() {
+        for source in ["", "   ", "!", "== 3", "two words", "gold = 3", "a b >= 3"] {
+            assert!(
+                FlagCondition::try_from(source.to_string()).is_err(),
+                "\"{source}\" should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn set_and_negation() {
+        let flags = flags();
+        assert!(holds("met_guard", &flags));
+        assert!(!holds("!met_guard", &flags));
+        assert!(holds("gold", &flags));
+        assert!(!holds("zero", &flags));
+        assert!(holds("!zero", &flags));
+    }
+
+    #[test]
+    fn each_comparison() {
+        let flags = flags();
+        assert!(holds("gold == 3", &flags));
+        assert!(!holds("gold == 4", &flags));
+        assert!(holds("gold != 4", &flags));
+        assert!(!holds("gold != 3", &flags));
+        assert!(holds("gold < 4", &flags));
+        assert!(!holds("gold < 3", &flags));
+        assert!(holds("gold <= 3", &flags));
+        assert!(!holds("gold <= 2", &flags));
+        assert!(holds("gold > 2", &flags));
+        assert!(!holds("gold > 3", &flags));
+        assert!(holds("gold >= 3", &flags));
+        assert!(!holds("gold >= 4", &flags));
+        assert!(holds("ending == good", &flags));
+        assert!(holds("ending != bad", &flags));
+    }
+
+    #[test]
+    fn missing_flags_count_as_false_and_zero() {
+        let flags = GameFlags::default();
+        assert!(!holds("missing", &flags));
+        assert!(holds("!missing", &flags));
+        assert!(holds("missing == false", &flags));
+        assert!(holds("missing == 0", &flags));
+        assert!(holds("missing == \"\"", &flags));
+        assert!(!holds("missing != 0", &flags));
+        assert!(holds("missing < 1", &flags));
+        assert!(holds("missing >= 0", &flags));
+        assert!(!holds("missing > 0", &flags));
+    }
+
+    #[test]
+    fn mismatched_types_are_never_equal_or_ordered() {
+        let flags = flags();
+        assert!(!holds("gold == three", &flags));
+        assert!(holds("gold != \"3\"", &flags));
+        assert!(!holds("met_guard == 1", &flags));
+        assert!(!holds("met_guard >= 1", &flags));
+        assert!(!holds("ending > 0", &flags));
+        assert!(!holds("ending < 0", &flags));
+        assert!(!holds("gold > true", &flags));
+    }
+}
@@ -0,0 +1,294 @@
+//! Player input as actions, each bound to keyboard keys and gamepad buttons. Systems check
+//! actions rather than particular keys, so the player can rebind them in the controls menu.
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<InputBindings>();
+    app.init_resource::<InputBindings>();
+}
+
+/// Something the player can do, whichever input it's bound to.
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Advances dialogue, picks choices and talks to whoever the player is facing.
+    Confirm,
+    /// Backs out of menus, or opens the pause menu during gameplay.
+    Cancel,
+    /// Opens and closes the pause menu.
+    Pause,
+    /// Opens the dialogue backlog.
+    Menu,
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    /// Toggles the debug overlay for UI.
+    #[cfg(feature = "dev")]
+    DebugUi,
+    /// Toggles the overlay listing the game flags.
+    #[cfg(feature = "dev")]
+    DebugFlags,
+}
+
+impl Action {
+    pub const ALL: &'static [Self] = &[
+        Self::Confirm,
+        Self::Cancel,
+        Self::Pause,
+        Self::Menu,
+        Self::MoveUp,
+        Self::MoveDown,
+        Self::MoveLeft,
+        Self::MoveRight,
+        #[cfg(feature = "dev")]
+        Self::DebugUi,
+        #[cfg(feature = "dev")]
+        Self::DebugFlags,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Confirm => "Confirm",
+            Self::Cancel => "Cancel",
+            Self::Pause => "Pause",
+            Self::Menu => "Backlog",
+            Self::MoveUp => "Move Up",
+            Self::MoveDown => "Move Down",
+            Self::MoveLeft => "Move Left",
+            Self::MoveRight => "Move Right",
+            #[cfg(feature = "dev")]
+            Self::DebugUi => "Debug UI",
+            #[cfg(feature = "dev")]
+            Self::DebugFlags => "Debug Flags",
+        }
+    }
+}
+
+/// A single key or gamepad button.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Binding {
+    Key(KeyCode),
+    Button(GamepadButton),
+}
+
+impl Binding {
+    /// A short name for the input, to show the player.
+    pub fn label(self) -> String {
+        match self {
+            Self::Key(key) => {
+                let name = format!("{key:?}");
+                ["Key", "Digit", "Arrow"]
+                    .iter()
+                    .find_map(|prefix| name.strip_prefix(prefix))
+                    .unwrap_or(&name)
+                    .to_string()
+            }
+            Self::Button(button) => format!("{button:?}"),
+        }
+    }
+}
+
+/// The keys and gamepad buttons bound to one [`Action`]. Any of them will do.
+#[derive(Reflect, Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActionBindings {
+    pub keys: Vec<KeyCode>,
+    pub buttons: Vec<GamepadButton>,
+}
+
+impl ActionBindings {
+    fn new<const K: usize, const B: usize>(
+        keys: [KeyCode; K],
+        buttons: [GamepadButton; B],
+    ) -> Self {
+        Self {
+            keys: keys.to_vec(),
+            buttons: buttons.to_vec(),
+        }
+    }
+
+    pub fn contains(&self, binding: Binding) -> bool {
+        match binding {
+            Binding::Key(key) => self.keys.contains(&key),
+            Binding::Button(button) => self.buttons.contains(&button),
+        }
+    }
+
+    /// Binds the action to `binding` in place of the input in `slot` on the same device, keeping
+    /// the others. Slots past the end add another input instead.
+    pub fn set(&mut self, slot: usize, binding: Binding) {
+        fn set_slot<T>(inputs: &mut Vec<T>, slot: usize, input: T) {
+            match inputs.get_mut(slot) {
+                Some(old) => *old = input,
+                None => inputs.push(input),
+            }
+        }
+
+        match binding {
+            Binding::Key(key) => set_slot(&mut self.keys, slot, key),
+            Binding::Button(button) => set_slot(&mut self.buttons, slot, button),
+        }
+    }
+}
+
+/// What every [`Action`] is bound to, changed in the controls menu. Actions missing from the
+/// config file keep their default bindings.
+#[derive(Resource, Reflect, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct InputBindings {
+    pub confirm: ActionBindings,
+    pub cancel: ActionBindings,
+    pub pause: ActionBindings,
+    pub menu: ActionBindings,
+    pub move_up: ActionBindings,
+    pub move_down: ActionBindings,
+    pub move_left: ActionBindings,
+    pub move_right: ActionBindings,
+    #[cfg(feature = "dev")]
+    pub debug_ui: ActionBindings,
+    #[cfg(feature = "dev")]
+    pub debug_flags: ActionBindings,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        Self {
+            confirm: ActionBindings::new(
+                [KeyCode::Space, KeyCode::Enter, KeyCode::KeyE],
+                [GamepadButton::South],
+            ),
+            cancel: ActionBindings::new([KeyCode::Escape], [GamepadButton::East]),
+            pause: ActionBindings::new([KeyCode::KeyP], [GamepadButton::Start]),
+            menu: ActionBindings::new([KeyCode::KeyL], [GamepadButton::Select]),
+            move_up: ActionBindings::new(
+                [KeyCode::KeyW, KeyCode::ArrowUp],
+                [GamepadButton::DPadUp],
+            ),
+            move_down: ActionBindings::new(
+                [KeyCode::KeyS, KeyCode::ArrowDown],
+                [GamepadButton::DPadDown],
+            ),
+            move_left: ActionBindings::new(
+                [KeyCode::KeyA, KeyCode::ArrowLeft],
+                [GamepadButton::DPadLeft],
+            ),
+            move_right: ActionBindings::new(
+                [KeyCode::KeyD, KeyCode::ArrowRight],
+                [GamepadButton::DPadRight],
+            ),
+            #[cfg(feature = "dev")]
+            debug_ui: ActionBindings::new([KeyCode::Backquote], []),
+            #[cfg(feature = "dev")]
+            debug_flags: ActionBindings::new([KeyCode::F1], []),
+        }
+    }
+}
+
+impl InputBindings {
+    pub fn get(&self, action: Action) -> &ActionBindings {
+        match action {
+            Action::Confirm => &self.confirm,
+            Action::Cancel => &self.cancel,
+            Action::Pause => &self.pause,
+            Action::Menu => &self.menu,
+            Action::MoveUp => &self.move_up,
+            Action::MoveDown => &self.move_down,
+            Action::MoveLeft => &self.move_left,
+            Action::MoveRight => &self.move_right,
+            #[cfg(feature = "dev")]
+            Action::DebugUi => &self.debug_ui,
+            #[cfg(feature = "dev")]
+            Action::DebugFlags => &self.debug_flags,
+        }
+    }
+
+    pub fn get_mut(&mut self, action: Action) -> &mut ActionBindings {
+        match action {
+            Action::Confirm => &mut self.confirm,
+            Action::Cancel => &mut self.cancel,
+            Action::Pause => &mut self.pause,
+            Action::Menu => &mut self.menu,
+            Action::MoveUp => &mut self.move_up,
+            Action::MoveDown => &mut self.move_down,
+            Action::MoveLeft => &mut self.move_left,
+            Action::MoveRight => &mut self.move_right,
+            #[cfg(feature = "dev")]
+            Action::DebugUi => &mut self.debug_ui,
+            #[cfg(feature = "dev")]
+            Action::DebugFlags => &mut self.debug_flags,
+        }
+    }
+
+    /// The action other than `action` that `binding` is already bound to, if any. Binding it to
+    /// `action` as well would leave one input doing two things.
+    pub fn conflict(&self, action: Action, binding: Binding) -> Option<Action> {
+        Action::ALL
+            .iter()
+            .copied()
+            .find(|other| *other != action && self.get(*other).contains(binding))
+    }
+}
+
+/// Reads whether actions are pressed, from the keyboard and every connected gamepad.
+#[derive(SystemParam)]
+pub struct ActionInput<'w, 's> {
+    bindings: Res<'w, InputBindings>,
+    keyboard: Res<'w, ButtonInput<KeyCode>>,
+    gamepads: Query<'w, 's, &'static Gamepad>,
+}
+
+impl ActionInput<'_, '_> {
+    pub fn pressed(&self, action: Action) -> bool {
+        let bindings = self.bindings.get(action);
+        self.keyboard.any_pressed(bindings.keys.iter().copied())
+            || self
+                .gamepads
+                .iter()
+                .any(|gamepad| gamepad.any_pressed(bindings.buttons.iter().copied()))
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        let bindings = self.bindings.get(action);
+        self.keyboard
+            .any_just_pressed(bindings.keys.iter().copied())
+            || self
+                .gamepads
+                .iter()
+                .any(|gamepad| gamepad.any_just_pressed(bindings.buttons.iter().copied()))
+    }
+
+    /// The connected gamepads, for reading their sticks.
+    pub fn gamepads(&self) -> impl Iterator<Item = &Gamepad> {
+        self.gamepads.iter()
+    }
+}
+
+/// A run condition that's true on the frame any input bound to `action` is pressed.
+pub fn action_just_pressed(action: Action) -> impl FnMut(ActionInput) -> bool + Clone {
+    move |input: ActionInput| input.just_pressed(action)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn setting_a_slot_keeps_the_other_bindings() {
+        let mut bindings = InputBindings::default().confirm;
+        bindings.set(0, Binding::Key(KeyCode::KeyZ));
+        assert_eq!(
+            bindings.keys,
+            vec![KeyCode::KeyZ, KeyCode::Enter, KeyCode::KeyE]
+        );
+        assert_eq!(bindings.buttons, vec![GamepadButton::South]);
+
+        bindings.set(1, Binding::Button(GamepadButton::West));
+        assert_eq!(
+            bindings.buttons,
+            vec![GamepadButton::South, GamepadButton::West]
+        );
+    }
+}
@@ -8,8 +8,14 @@ mod audio;
 mod demo;
 #[cfg(feature = "dev")]
 mod dev_tools;
+mod dialogue;
+mod game_flags;
+mod input;
 mod menus;
+mod save;
 mod screens;
+mod settings;
+mod storage;
 mod text_boxes;
 mod theme;
 
@@ -51,8 +57,13 @@ impl Plugin for AppPlugin {
             demo::plugin,
             #[cfg(feature = "dev")]
             dev_tools::plugin,
+            dialogue::plugin,
+            game_flags::plugin,
+            input::plugin,
             menus::plugin,
+            save::plugin,
             screens::plugin,
+            settings::plugin,
             theme::plugin,
             text_boxes::plugin,
         ));
@@ -71,6 +82,9 @@ impl Plugin for AppPlugin {
         // Set up the `Pause` state.
         app.init_state::<Pause>();
         app.configure_sets(Update, PausableSystems.run_if(in_state(Pause(false))));
+        // Stop virtual time as well, so anything timed with it carries on where it left off.
+        app.add_systems(OnEnter(Pause(true)), pause_virtual_time);
+        app.add_systems(OnExit(Pause(true)), unpause_virtual_time);
 
         // Spawn the main camera.
         app.add_systems(Startup, spawn_camera);
@@ -99,6 +113,14 @@ struct Pause(pub bool);
 #[derive(SystemSet, Copy, Clone, Eq, PartialEq, Hash, Debug)]
 struct PausableSystems;
 
+fn pause_virtual_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+fn unpause_virtual_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
 fn spawn_camera(mut commands: Commands) {
     commands.spawn((Name::new("Camera"), Camera2d));
 }
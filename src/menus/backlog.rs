@@ -0,0 +1,132 @@
+//! The dialogue backlog, for reading back earlier lines.
+
+use bevy::{
+    ecs::spawn::SpawnWith,
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+    ui::Val::*,
+};
+
+use crate::{
+    input::{Action, ActionInput, action_just_pressed},
+    menus::Menu,
+    text_boxes::{DialogueHistory, DialogueHistoryEntry},
+    theme::widget,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<BacklogEntries>();
+    app.add_systems(OnEnter(Menu::Backlog), spawn_backlog_menu);
+    app.add_systems(
+        Update,
+        (
+            go_back
+                .run_if(action_just_pressed(Action::Cancel).or(action_just_pressed(Action::Menu))),
+            scroll_backlog,
+        )
+            .run_if(in_state(Menu::Backlog)),
+    );
+}
+
+fn spawn_backlog_menu(mut commands: Commands, history: Res<DialogueHistory>) {
+    let entries = history.entries.clone();
+    commands.spawn((
+        widget::ui_root("Backlog Menu"),
+        GlobalZIndex(2),
+        StateScoped(Menu::Backlog),
+        children![
+            widget::header("Backlog"),
+            (
+                Name::new("Backlog Entries"),
+                BacklogEntries,
+                Node {
+                    width: Px(900.0),
+                    height: Percent(60.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Px(16.0),
+                    overflow: Overflow::scroll_y(),
+                    ..default()
+                },
+                // start at the most recent line, layout clamps this to the end of the list
+                ScrollPosition {
+                    offset_y: f32::MAX,
+                    ..default()
+                },
+                Children::spawn(SpawnWith(move |parent: &mut ChildSpawner| {
+                    if entries.is_empty() {
+                        parent.spawn(widget::label("Nothing has been said yet."));
+                    }
+                    for entry in entries {
+                        parent.spawn(backlog_entry(entry));
+                    }
+                })),
+            ),
+            widget::button("Back", go_back_on_click),
+        ],
+    ));
+}
+
+fn backlog_entry(entry: DialogueHistoryEntry) -> impl Bundle {
+    let seconds = entry.time_s as u32;
+    let heading = match entry.speaker {
+        Some(speaker) => format!("{:02}:{:02}  {speaker}", seconds / 60, seconds % 60),
+        None => format!("{:02}:{:02}", seconds / 60, seconds % 60),
+    };
+    (
+        Name::new("Backlog Entry"),
+        Node {
+            flex_direction: FlexDirection::Column,
+            flex_shrink: 0.0,
+            ..default()
+        },
+        children![
+            widget::label(heading),
+            (
+                Name::new("Backlog Entry Text"),
+                Node {
+                    padding: UiRect::left(Px(20.0)),
+                    ..default()
+                },
+                children![widget::label(entry.text)],
+            ),
+        ],
+    )
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct BacklogEntries;
+
+/// How far a line of mouse wheel scrolling or a move up/down press moves the backlog.
+const SCROLL_LINE_HEIGHT: f32 = 30.0;
+
+fn scroll_backlog(
+    mut wheel_events: EventReader<MouseWheel>,
+    input: ActionInput,
+    mut scroll_position: Single<&mut ScrollPosition, With<BacklogEntries>>,
+) {
+    let mut offset = 0.0;
+    for event in wheel_events.read() {
+        offset -= match event.unit {
+            MouseScrollUnit::Line => event.y * SCROLL_LINE_HEIGHT,
+            MouseScrollUnit::Pixel => event.y,
+        };
+    }
+    if input.just_pressed(Action::MoveUp) {
+        offset -= SCROLL_LINE_HEIGHT;
+    }
+    if input.just_pressed(Action::MoveDown) {
+        offset += SCROLL_LINE_HEIGHT;
+    }
+    if offset != 0.0 {
+        scroll_position.offset_y = (scroll_position.offset_y + offset).max(0.0);
+    }
+}
+
+fn go_back_on_click(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::None);
+}
+
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::None);
+}
@@ -0,0 +1,288 @@
+//! The controls menu (seen from the settings menu), for rebinding actions.
+
+use bevy::{ecs::spawn::SpawnWith, input::InputSystem, prelude::*, ui::Val::*};
+
+use crate::{
+    input::{Action, Binding, action_just_pressed},
+    menus::Menu,
+    settings::Settings,
+    theme::widget,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Rebinding>();
+    app.register_type::<BindingButton>();
+    app.register_type::<ControlsStatus>();
+    app.add_systems(OnEnter(Menu::Controls), spawn_controls_menu);
+    app.add_systems(OnExit(Menu::Controls), stop_rebinding);
+    app.add_systems(
+        Update,
+        (
+            go_back.run_if(action_just_pressed(Action::Cancel)),
+            update_binding_buttons,
+        )
+            .run_if(in_state(Menu::Controls)),
+    );
+    // Take the input before anything else sees it, so a key that's already bound doesn't go off
+    // while it's being picked.
+    app.add_systems(
+        PreUpdate,
+        capture_binding
+            .after(InputSystem)
+            .run_if(resource_exists::<Rebinding>),
+    );
+}
+
+/// The binding waiting for the player to press a new input for it.
+#[derive(Resource, Reflect, Debug, Clone, Copy)]
+#[reflect(Resource)]
+struct Rebinding {
+    action: Action,
+    device: InputDevice,
+    slot: usize,
+}
+
+/// How many keys and gamepad buttons each action can be bound to from the menu.
+const KEY_SLOTS: usize = 3;
+const BUTTON_SLOTS: usize = 2;
+
+/// Stops waiting for a new binding and keeps the old one. It can't be picked as a binding itself.
+const CANCEL_REBINDING_KEY: KeyCode = KeyCode::Escape;
+
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+enum InputDevice {
+    Keyboard,
+    Gamepad,
+}
+
+/// Shows and changes one of the inputs `action` is bound to on `device`.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct BindingButton {
+    action: Action,
+    device: InputDevice,
+    slot: usize,
+}
+
+/// Tells the player what to press, or why a new binding didn't take.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ControlsStatus;
+
+fn spawn_controls_menu(mut commands: Commands) {
+    commands.spawn((
+        widget::ui_root("Controls Menu"),
+        GlobalZIndex(2),
+        StateScoped(Menu::Controls),
+        children![
+            widget::header("Controls"),
+            (
+                widget::label("Click a binding to change it, or an empty slot to add one."),
+                ControlsStatus
+            ),
+            controls_grid(),
+            (
+                Name::new("Controls Buttons"),
+                Node {
+                    column_gap: Px(30.0),
+                    ..default()
+                },
+                children![
+                    widget::button("Reset", reset_bindings),
+                    widget::button("Back", go_back_on_click),
+                ],
+            ),
+        ],
+    ));
+}
+
+fn controls_grid() -> impl Bundle {
+    (
+        Name::new("Controls Grid"),
+        Node {
+            display: Display::Grid,
+            row_gap: Px(6.0),
+            column_gap: Px(10.0),
+            grid_template_columns: vec![
+                RepeatedGridTrack::px(1, 160.0),
+                RepeatedGridTrack::px((KEY_SLOTS + BUTTON_SLOTS) as u16, 150.0),
+            ],
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        Children::spawn(SpawnWith(|parent: &mut ChildSpawner| {
+            parent.spawn(widget::label(""));
+            parent.spawn(device_header("Keyboard", KEY_SLOTS));
+            parent.spawn(device_header("Gamepad", BUTTON_SLOTS));
+            for &action in Action::ALL {
+                parent.spawn((
+                    widget::label(action.label()),
+                    Node {
+                        justify_self: JustifySelf::End,
+                        ..default()
+                    },
+                ));
+                for slot in 0..KEY_SLOTS {
+                    parent.spawn(binding_button(action, InputDevice::Keyboard, slot));
+                }
+                for slot in 0..BUTTON_SLOTS {
+                    parent.spawn(binding_button(action, InputDevice::Gamepad, slot));
+                }
+            }
+        })),
+    )
+}
+
+/// A column header spanning the binding slots of one device.
+fn device_header(text: &'static str, slots: usize) -> impl Bundle {
+    (
+        widget::label(text),
+        Node {
+            grid_column: GridPlacement::span(slots as u16),
+            justify_self: JustifySelf::Center,
+            ..default()
+        },
+    )
+}
+
+fn binding_button(action: Action, device: InputDevice, slot: usize) -> impl Bundle {
+    (
+        widget::button_medium(
+            "",
+            move |_: Trigger<Pointer<Click>>,
+                  mut commands: Commands,
+                  mut status: Single<&mut Text, With<ControlsStatus>>| {
+                commands.insert_resource(Rebinding {
+                    action,
+                    device,
+                    slot,
+                });
+                let input = match device {
+                    InputDevice::Keyboard => "a key",
+                    InputDevice::Gamepad => "a gamepad button",
+                };
+                status.0 = format!(
+                    "Press {input} for {}, or {} to cancel.",
+                    action.label(),
+                    Binding::Key(CANCEL_REBINDING_KEY).label()
+                );
+            },
+        ),
+        BindingButton {
+            action,
+            device,
+            slot,
+        },
+    )
+}
+
+fn update_binding_buttons(
+    settings: Res<Settings>,
+    rebinding: Option<Res<Rebinding>>,
+    button_query: Query<(Entity, &BindingButton)>,
+    children_query: Query<&Children>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (entity, button) in &button_query {
+        let waiting = rebinding.as_ref().is_some_and(|rebinding| {
+            rebinding.action == button.action
+                && rebinding.device == button.device
+                && rebinding.slot == button.slot
+        });
+        let bindings = settings.bindings.get(button.action);
+        let binding = match button.device {
+            InputDevice::Keyboard => bindings.keys.get(button.slot).copied().map(Binding::Key),
+            InputDevice::Gamepad => bindings
+                .buttons
+                .get(button.slot)
+                .copied()
+                .map(Binding::Button),
+        };
+        let label = if waiting {
+            "...".to_string()
+        } else {
+            binding.map_or_else(|| "-".to_string(), Binding::label)
+        };
+
+        let mut texts = text_query.iter_many_mut(children_query.iter_descendants(entity));
+        while let Some(mut text) = texts.fetch_next() {
+            if text.0 != label {
+                text.0.clone_from(&label);
+            }
+        }
+    }
+}
+
+/// Binds the slot waiting in [`Rebinding`] to the first input pressed on its device, unless the
+/// input is already in use. [`CANCEL_REBINDING_KEY`] stops waiting without changing anything.
+fn capture_binding(
+    mut commands: Commands,
+    rebinding: Res<Rebinding>,
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+    mut gamepads: Query<&mut Gamepad>,
+    mut settings: ResMut<Settings>,
+    mut status: Single<&mut Text, With<ControlsStatus>>,
+) {
+    if keyboard.just_pressed(CANCEL_REBINDING_KEY) {
+        keyboard.clear_just_pressed(CANCEL_REBINDING_KEY);
+        commands.remove_resource::<Rebinding>();
+        status.0 = "Kept the old binding.".to_string();
+        return;
+    }
+    let binding = match rebinding.device {
+        InputDevice::Keyboard => {
+            let Some(key) = keyboard.get_just_pressed().next().copied() else {
+                return;
+            };
+            keyboard.clear_just_pressed(key);
+            Binding::Key(key)
+        }
+        InputDevice::Gamepad => {
+            let Some(button) = gamepads.iter_mut().find_map(|mut gamepad| {
+                let button = gamepad.get_just_pressed().next().copied()?;
+                gamepad.digital_mut().clear_just_pressed(button);
+                Some(button)
+            }) else {
+                return;
+            };
+            Binding::Button(button)
+        }
+    };
+    commands.remove_resource::<Rebinding>();
+
+    let action = rebinding.action;
+    let bindings = &mut settings.bindings;
+    let taken_by = bindings
+        .conflict(action, binding)
+        .or_else(|| bindings.get(action).contains(binding).then_some(action));
+    status.0 = match taken_by {
+        Some(other) => format!("{} is already bound to {}.", binding.label(), other.label()),
+        None => {
+            bindings.get_mut(action).set(rebinding.slot, binding);
+            format!("{} is now bound to {}.", action.label(), binding.label())
+        }
+    };
+}
+
+fn stop_rebinding(mut commands: Commands) {
+    commands.remove_resource::<Rebinding>();
+}
+
+fn reset_bindings(
+    _: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    mut settings: ResMut<Settings>,
+    mut status: Single<&mut Text, With<ControlsStatus>>,
+) {
+    commands.remove_resource::<Rebinding>();
+    settings.bindings = default();
+    status.0 = "Controls reset to the defaults.".to_string();
+}
+
+fn go_back_on_click(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Settings);
+}
+
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Settings);
+}
@@ -0,0 +1,82 @@
+//! The load game menu (seen on the title screen).
+
+use bevy::{ecs::spawn::SpawnWith, prelude::*, ui::Val::*};
+
+use crate::{
+    asset_tracking::ResourceHandles,
+    input::{Action, action_just_pressed},
+    menus::Menu,
+    save::{self, PendingLoad, SAVE_SLOTS},
+    screens::Screen,
+    theme::widget,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::Load), spawn_load_menu);
+    app.add_systems(
+        Update,
+        go_back.run_if(in_state(Menu::Load).and(action_just_pressed(Action::Cancel))),
+    );
+}
+
+fn spawn_load_menu(mut commands: Commands) {
+    commands.spawn((
+        widget::ui_root("Load Menu"),
+        GlobalZIndex(2),
+        StateScoped(Menu::Load),
+        children![
+            widget::header("Load game"),
+            save_slots(),
+            widget::button("Back", go_back_on_click),
+        ],
+    ));
+}
+
+fn save_slots() -> impl Bundle {
+    (
+        Name::new("Save Slots"),
+        Node {
+            display: Display::Grid,
+            row_gap: Px(10.0),
+            column_gap: Px(30.0),
+            grid_template_columns: RepeatedGridTrack::px(2, 400.0),
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        Children::spawn(SpawnWith(|parent: &mut ChildSpawner| {
+            for slot in 0..SAVE_SLOTS {
+                parent.spawn(widget::button(
+                    format!("Slot {}", slot + 1),
+                    move |_: Trigger<Pointer<Click>>,
+                          mut commands: Commands,
+                          resource_handles: Res<ResourceHandles>,
+                          mut next_screen: ResMut<NextState<Screen>>| {
+                        let data = match save::read_slot(slot) {
+                            Ok(Some(data)) => data,
+                            Ok(None) => return,
+                            Err(error) => {
+                                error!("Could not load save slot {}: {error}", slot + 1);
+                                return;
+                            }
+                        };
+                        commands.insert_resource(PendingLoad(data));
+                        next_screen.set(if resource_handles.is_all_done() {
+                            Screen::Gameplay
+                        } else {
+                            Screen::Loading
+                        });
+                    },
+                ));
+                parent.spawn(widget::label(save::slot_summary(slot)));
+            }
+        })),
+    )
+}
+
+fn go_back_on_click(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Main);
+}
+
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Main);
+}
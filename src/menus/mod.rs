@@ -1,8 +1,12 @@
 //! The game's menus and transitions between them.
 
+mod backlog;
+mod controls;
 mod credits;
+mod load;
 mod main;
 mod pause;
+mod save;
 mod settings;
 
 use bevy::prelude::*;
@@ -11,8 +15,12 @@ pub(super) fn plugin(app: &mut App) {
     app.init_state::<Menu>();
 
     app.add_plugins((
+        backlog::plugin,
+        controls::plugin,
         credits::plugin,
+        load::plugin,
         main::plugin,
+        save::plugin,
         settings::plugin,
         pause::plugin,
     ));
@@ -26,5 +34,9 @@ pub enum Menu {
     Main,
     Credits,
     Settings,
+    Controls,
     Pause,
+    Backlog,
+    Save,
+    Load,
 }
@@ -0,0 +1,108 @@
+//! The save game menu (seen from the pause menu).
+
+use bevy::{ecs::spawn::SpawnWith, prelude::*, ui::Val::*};
+
+use crate::{
+    demo::{cutscene::CutsceneRunner, level::LevelAssets, movement::GridMover, player::Player},
+    game_flags::GameFlags,
+    input::{Action, action_just_pressed},
+    menus::Menu,
+    save::{self, PlayTime, SAVE_SLOTS, SaveData},
+    text_boxes::TextBox,
+    theme::widget,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<SaveMenu>();
+    app.add_systems(OnEnter(Menu::Save), spawn_save_menu);
+    app.add_systems(
+        Update,
+        go_back.run_if(in_state(Menu::Save).and(action_just_pressed(Action::Cancel))),
+    );
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct SaveMenu;
+
+fn spawn_save_menu(mut commands: Commands) {
+    commands.spawn(save_menu(String::new()));
+}
+
+/// The menu, with `status` shown under the header. The slots are read as it spawns, so spawning
+/// it again shows what was just saved.
+fn save_menu(status: String) -> impl Bundle {
+    (
+        widget::ui_root("Save Menu"),
+        SaveMenu,
+        GlobalZIndex(2),
+        StateScoped(Menu::Save),
+        children![
+            widget::header("Save game"),
+            widget::label(status),
+            save_slots(),
+            widget::button("Back", go_back_on_click),
+        ],
+    )
+}
+
+fn save_slots() -> impl Bundle {
+    (
+        Name::new("Save Slots"),
+        Node {
+            display: Display::Grid,
+            row_gap: Px(10.0),
+            column_gap: Px(30.0),
+            grid_template_columns: RepeatedGridTrack::px(2, 400.0),
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        Children::spawn(SpawnWith(|parent: &mut ChildSpawner| {
+            for slot in 0..SAVE_SLOTS {
+                parent.spawn(widget::button(
+                    format!("Slot {}", slot + 1),
+                    move |_: Trigger<Pointer<Click>>,
+                          mut commands: Commands,
+                          menu: Single<Entity, With<SaveMenu>>,
+                          cutscene_query: Query<(), With<CutsceneRunner>>,
+                          text_box_query: Query<(), With<TextBox>>,
+                          player: Single<&GridMover, With<Player>>,
+                          level_assets: Res<LevelAssets>,
+                          flags: Res<GameFlags>,
+                          play_time: Res<PlayTime>| {
+                        let status = if !cutscene_query.is_empty() {
+                            "You can't save during a cutscene.".to_string()
+                        } else if !text_box_query.is_empty() {
+                            "You can't save during a conversation.".to_string()
+                        } else {
+                            let map = level_assets
+                                .map
+                                .path()
+                                .map(ToString::to_string)
+                                .unwrap_or_default();
+                            let data = SaveData::new(map, &player, &flags, &play_time);
+                            match save::write_slot(slot, &data) {
+                                Ok(()) => format!("Saved to slot {}.", slot + 1),
+                                Err(error) => {
+                                    error!("Could not save to slot {}: {error}", slot + 1);
+                                    "Saving failed.".to_string()
+                                }
+                            }
+                        };
+                        commands.entity(*menu).despawn();
+                        commands.spawn(save_menu(status));
+                    },
+                ));
+                parent.spawn(widget::label(save::slot_summary(slot)));
+            }
+        })),
+    )
+}
+
+fn go_back_on_click(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Pause);
+}
+
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Pause);
+}
@@ -2,21 +2,45 @@
 //!
 //! Additional settings and accessibility options should go here.
 
-use bevy::{audio::Volume, input::common_conditions::input_just_pressed, prelude::*, ui::Val::*};
+use bevy::{prelude::*, ui::Val::*};
 
-use crate::{menus::Menu, screens::Screen, theme::prelude::*};
+use crate::{
+    input::{Action, action_just_pressed},
+    menus::Menu,
+    screens::Screen,
+    settings::Settings,
+    theme::prelude::*,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Menu::Settings), spawn_settings_menu);
     app.add_systems(
         Update,
-        go_back.run_if(in_state(Menu::Settings).and(input_just_pressed(KeyCode::Escape))),
+        go_back.run_if(in_state(Menu::Settings).and(action_just_pressed(Action::Cancel))),
     );
 
-    app.register_type::<GlobalVolumeLabel>();
+    app.register_type::<VolumeLabel>();
     app.add_systems(
         Update,
-        update_global_volume_label.run_if(in_state(Menu::Settings)),
+        update_volume_labels.run_if(in_state(Menu::Settings)),
+    );
+
+    app.register_type::<DialoguePlaybackLabel>();
+    app.add_systems(
+        Update,
+        update_dialogue_playback_label.run_if(in_state(Menu::Settings)),
+    );
+
+    app.register_type::<TextSpeedLabel>();
+    app.add_systems(
+        Update,
+        update_text_speed_label.run_if(in_state(Menu::Settings)),
+    );
+
+    app.register_type::<WindowModeLabel>();
+    app.add_systems(
+        Update,
+        update_window_mode_label.run_if(in_state(Menu::Settings)),
     );
 }
 
@@ -28,6 +52,7 @@ fn spawn_settings_menu(mut commands: Commands) {
         children![
             widget::header("Settings"),
             settings_grid(),
+            widget::button("Controls", open_controls_menu),
             widget::button("Back", go_back_on_click),
         ],
     ));
@@ -51,20 +76,92 @@ fn settings_grid() -> impl Bundle {
                     ..default()
                 }
             ),
-            global_volume_widget(),
+            volume_widget(VolumeChannel::Master),
+            (
+                widget::label("Music Volume"),
+                Node {
+                    justify_self: JustifySelf::End,
+                    ..default()
+                }
+            ),
+            volume_widget(VolumeChannel::Music),
+            (
+                widget::label("SFX Volume"),
+                Node {
+                    justify_self: JustifySelf::End,
+                    ..default()
+                }
+            ),
+            volume_widget(VolumeChannel::SoundEffects),
+            (
+                widget::label("Dialogue"),
+                Node {
+                    justify_self: JustifySelf::End,
+                    ..default()
+                }
+            ),
+            dialogue_playback_widget(),
+            (
+                widget::label("Text Speed"),
+                Node {
+                    justify_self: JustifySelf::End,
+                    ..default()
+                }
+            ),
+            text_speed_widget(),
+            (
+                widget::label("Window"),
+                Node {
+                    justify_self: JustifySelf::End,
+                    ..default()
+                }
+            ),
+            window_mode_widget(),
         ],
     )
 }
 
-fn global_volume_widget() -> impl Bundle {
+/// One of the volumes in [`Settings`].
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+enum VolumeChannel {
+    Master,
+    Music,
+    SoundEffects,
+}
+
+impl VolumeChannel {
+    fn volume(self, settings: &Settings) -> f32 {
+        match self {
+            Self::Master => settings.master_volume,
+            Self::Music => settings.music_volume,
+            Self::SoundEffects => settings.sound_effect_volume,
+        }
+    }
+
+    fn volume_mut(self, settings: &mut Settings) -> &mut f32 {
+        match self {
+            Self::Master => &mut settings.master_volume,
+            Self::Music => &mut settings.music_volume,
+            Self::SoundEffects => &mut settings.sound_effect_volume,
+        }
+    }
+}
+
+fn volume_widget(channel: VolumeChannel) -> impl Bundle {
     (
-        Name::new("Global Volume Widget"),
+        Name::new(format!("{channel:?} Volume Widget")),
         Node {
             justify_self: JustifySelf::Start,
             ..default()
         },
         children![
-            widget::button_small("-", lower_global_volume),
+            widget::button_small(
+                "-",
+                move |_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>| {
+                    let volume = channel.volume_mut(&mut settings);
+                    *volume = (*volume - 0.1).max(MIN_VOLUME);
+                }
+            ),
             (
                 Name::new("Current Volume"),
                 Node {
@@ -72,9 +169,15 @@ fn global_volume_widget() -> impl Bundle {
                     justify_content: JustifyContent::Center,
                     ..default()
                 },
-                children![(widget::label(""), GlobalVolumeLabel)],
+                children![(widget::label(""), VolumeLabel(channel))],
+            ),
+            widget::button_small(
+                "+",
+                move |_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>| {
+                    let volume = channel.volume_mut(&mut settings);
+                    *volume = (*volume + 0.1).min(MAX_VOLUME);
+                }
             ),
-            widget::button_small("+", raise_global_volume),
         ],
     )
 }
@@ -82,26 +185,149 @@ fn global_volume_widget() -> impl Bundle {
 const MIN_VOLUME: f32 = 0.0;
 const MAX_VOLUME: f32 = 3.0;
 
-fn lower_global_volume(_: Trigger<Pointer<Click>>, mut global_volume: ResMut<GlobalVolume>) {
-    let linear = (global_volume.volume.to_linear() - 0.1).max(MIN_VOLUME);
-    global_volume.volume = Volume::Linear(linear);
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct VolumeLabel(VolumeChannel);
+
+fn update_volume_labels(
+    settings: Res<Settings>,
+    mut label_query: Query<(&mut Text, &VolumeLabel)>,
+) {
+    for (mut text, label) in &mut label_query {
+        let percent = 100.0 * label.0.volume(&settings);
+        text.0 = format!("{percent:3.0}%");
+    }
 }
 
-fn raise_global_volume(_: Trigger<Pointer<Click>>, mut global_volume: ResMut<GlobalVolume>) {
-    let linear = (global_volume.volume.to_linear() + 0.1).min(MAX_VOLUME);
-    global_volume.volume = Volume::Linear(linear);
+fn dialogue_playback_widget() -> impl Bundle {
+    (
+        Name::new("Dialogue Playback Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small("<", previous_dialogue_playback),
+            (
+                Name::new("Current Dialogue Playback"),
+                Node {
+                    padding: UiRect::horizontal(Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), DialoguePlaybackLabel)],
+            ),
+            widget::button_small(">", next_dialogue_playback),
+        ],
+    )
+}
+
+fn previous_dialogue_playback(_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>) {
+    settings.dialogue_playback = settings.dialogue_playback.previous();
+}
+
+fn next_dialogue_playback(_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>) {
+    settings.dialogue_playback = settings.dialogue_playback.next();
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct DialoguePlaybackLabel;
+
+fn update_dialogue_playback_label(
+    settings: Res<Settings>,
+    mut label: Single<&mut Text, With<DialoguePlaybackLabel>>,
+) {
+    label.0 = settings.dialogue_playback.label().to_string();
+}
+
+fn text_speed_widget() -> impl Bundle {
+    (
+        Name::new("Text Speed Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small("<", slower_text_speed),
+            (
+                Name::new("Current Text Speed"),
+                Node {
+                    padding: UiRect::horizontal(Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), TextSpeedLabel)],
+            ),
+            widget::button_small(">", faster_text_speed),
+        ],
+    )
+}
+
+fn slower_text_speed(_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>) {
+    settings.text_speed = settings.text_speed.previous();
+}
+
+fn faster_text_speed(_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>) {
+    settings.text_speed = settings.text_speed.next();
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct TextSpeedLabel;
+
+fn update_text_speed_label(
+    settings: Res<Settings>,
+    mut label: Single<&mut Text, With<TextSpeedLabel>>,
+) {
+    label.0 = settings.text_speed.label().to_string();
+}
+
+fn window_mode_widget() -> impl Bundle {
+    (
+        Name::new("Window Mode Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small("<", toggle_fullscreen),
+            (
+                Name::new("Current Window Mode"),
+                Node {
+                    padding: UiRect::horizontal(Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), WindowModeLabel)],
+            ),
+            widget::button_small(">", toggle_fullscreen),
+        ],
+    )
+}
+
+fn toggle_fullscreen(_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>) {
+    settings.fullscreen = !settings.fullscreen;
 }
 
 #[derive(Component, Reflect)]
 #[reflect(Component)]
-struct GlobalVolumeLabel;
+struct WindowModeLabel;
 
-fn update_global_volume_label(
-    global_volume: Res<GlobalVolume>,
-    mut label: Single<&mut Text, With<GlobalVolumeLabel>>,
+fn update_window_mode_label(
+    settings: Res<Settings>,
+    mut label: Single<&mut Text, With<WindowModeLabel>>,
 ) {
-    let percent = 100.0 * global_volume.volume.to_linear();
-    label.0 = format!("{percent:3.0}%");
+    label.0 = if settings.fullscreen {
+        "Fullscreen"
+    } else {
+        "Windowed"
+    }
+    .to_string();
+}
+
+fn open_controls_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Controls);
 }
 
 fn go_back_on_click(
@@ -0,0 +1,323 @@
+//! Saving the game to a handful of slots, and picking up from one of them later.
+
+use std::{collections::HashMap, time::Duration};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    AppSystems, PausableSystems,
+    demo::{
+        level::{LevelAssets, spawn_level},
+        map::TileGrid,
+        movement::{ACTOR_Z, GridMover},
+        player::Player,
+    },
+    game_flags::{FlagValue, GameFlags, reset_game_flags},
+    screens::Screen,
+    storage::{self, StorageDir},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<PlayTime>();
+    app.init_resource::<PlayTime>();
+
+    app.add_systems(
+        Update,
+        tick_play_time
+            .in_set(AppSystems::TickTimers)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+
+    // Flags are restored before the level spawns, so it can tell what has already happened.
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        (
+            (
+                reset_play_time,
+                restore_progress
+                    .after(reset_game_flags)
+                    .run_if(resource_exists::<PendingLoad>),
+            )
+                .chain()
+                .before(spawn_level),
+            // the save is used up even if the player couldn't be put back
+            (restore_player, clear_pending_load)
+                .chain()
+                .after(spawn_level)
+                .run_if(resource_exists::<PendingLoad>),
+        ),
+    );
+}
+
+/// How many save slots there are.
+pub const SAVE_SLOTS: usize = 3;
+
+/// Bumped whenever [`SaveData`] changes shape.
+const SAVE_VERSION: u32 = 2;
+
+/// Everything needed to pick the game back up, as written to a save slot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveData {
+    pub version: u32,
+    /// Seconds since the Unix epoch.
+    pub saved_at: u64,
+    pub play_time_s: f64,
+    /// The asset path of the map the player was on.
+    pub map: String,
+    pub player_position: IVec2,
+    pub player_facing: IVec2,
+    pub flags: HashMap<String, FlagValue>,
+    /// The ids of the characters in the party. Nothing fills this in yet.
+    #[serde(default)]
+    pub party: Vec<String>,
+    /// How many of each item is held, by item id. Nothing fills this in yet.
+    #[serde(default)]
+    pub inventory: HashMap<String, u32>,
+}
+
+impl SaveData {
+    pub fn new(map: String, player: &GridMover, flags: &GameFlags, play_time: &PlayTime) -> Self {
+        Self {
+            version: SAVE_VERSION,
+            saved_at: now(),
+            play_time_s: play_time.0.as_secs_f64(),
+            map,
+            player_position: player.position,
+            player_facing: player.facing,
+            flags: flags.0.clone(),
+            party: Vec::new(),
+            inventory: HashMap::new(),
+        }
+    }
+
+    /// When the save was made and how long had been played, for listing it in a menu.
+    pub fn summary(&self) -> String {
+        let seconds = self.play_time_s as u64;
+        format!(
+            "{}\nPlayed {}:{:02}:{:02}",
+            format_timestamp(self.saved_at),
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60,
+        )
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SaveError {
+    #[error("could not access save slot: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse save: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("could not write save: {0}")]
+    Write(#[from] ron::Error),
+    #[error("save was made by a newer version of the game (format {0})")]
+    TooNew(u32),
+}
+
+/// Reads the save in `slot`, or `None` if nothing has been saved there yet.
+pub fn read_slot(slot: usize) -> Result<Option<SaveData>, SaveError> {
+    match storage::read(StorageDir::Data, &slot_file(slot))? {
+        Some(contents) => parse_save(&contents).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn parse_save(contents: &str) -> Result<SaveData, SaveError> {
+    #[derive(Deserialize)]
+    struct SaveVersion {
+        version: u32,
+    }
+
+    let SaveVersion { version } = ron::from_str(contents)?;
+    if version > SAVE_VERSION {
+        return Err(SaveError::TooNew(version));
+    }
+    // version 1 saves have no party or inventory, which start out empty
+    Ok(ron::from_str(contents)?)
+}
+
+pub fn write_slot(slot: usize, data: &SaveData) -> Result<(), SaveError> {
+    let contents = ron::ser::to_string_pretty(data, default())?;
+    storage::write(StorageDir::Data, &slot_file(slot), &contents)?;
+    Ok(())
+}
+
+fn slot_file(slot: usize) -> String {
+    format!("saves/slot_{}.ron", slot + 1)
+}
+
+/// What to show for `slot` in a list of saves.
+pub fn slot_summary(slot: usize) -> String {
+    match read_slot(slot) {
+        Ok(Some(data)) => data.summary(),
+        Ok(None) => "Empty".to_string(),
+        Err(error) => {
+            warn!("Could not read save slot {}: {error}", slot + 1);
+            "Unreadable save".to_string()
+        }
+    }
+}
+
+/// A save to restore when the gameplay screen is next entered.
+#[derive(Resource, Debug)]
+pub struct PendingLoad(pub SaveData);
+
+/// How long has been played, not counting time spent paused.
+#[derive(Resource, Reflect, Debug, Default)]
+#[reflect(Resource)]
+pub struct PlayTime(pub Duration);
+
+fn tick_play_time(time: Res<Time>, mut play_time: ResMut<PlayTime>) {
+    play_time.0 += time.delta();
+}
+
+fn reset_play_time(mut play_time: ResMut<PlayTime>) {
+    play_time.0 = Duration::ZERO;
+}
+
+fn restore_progress(
+    pending: Res<PendingLoad>,
+    mut flags: ResMut<GameFlags>,
+    mut play_time: ResMut<PlayTime>,
+) {
+    flags.0 = pending.0.flags.clone();
+    play_time.0 = Duration::from_secs_f64(pending.0.play_time_s);
+}
+
+/// Puts the player back where they were saved. They stay at the map's start instead if the save
+/// was made on another map, or its position isn't somewhere they can stand.
+fn restore_player(
+    pending: Res<PendingLoad>,
+    level_assets: Res<LevelAssets>,
+    mut player_query: Query<(&mut GridMover, &mut Transform), With<Player>>,
+    grid_query: Query<&TileGrid>,
+) {
+    let map = level_assets.map.path().map(ToString::to_string);
+    if map.as_ref() != Some(&pending.0.map) {
+        warn!(
+            "Save was made on map \"{}\", which isn't loaded, so the player stays at the start",
+            pending.0.map
+        );
+        return;
+    }
+    let (Ok((mut mover, mut transform)), Ok(grid)) =
+        (player_query.single_mut(), grid_query.single())
+    else {
+        warn!("Could not restore the player's position, the level has no player or map");
+        return;
+    };
+    let position = pending.0.player_position;
+    if grid.is_solid(position) {
+        warn!("Saved player position {position} isn't walkable, so the player stays at the start");
+        return;
+    }
+    mover.position = position;
+    mover.facing = pending.0.player_facing;
+    transform.translation = grid.tile_to_world(position).extend(ACTOR_Z);
+}
+
+fn clear_pending_load(mut commands: Commands) {
+    commands.remove_resource::<PendingLoad>();
+}
+
+/// Seconds since the Unix epoch.
+#[cfg(not(target_family = "wasm"))]
+fn now() -> u64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs())
+}
+
+/// Seconds since the Unix epoch.
+#[cfg(target_family = "wasm")]
+fn now() -> u64 {
+    (js_sys::Date::now() / 1000.) as u64
+}
+
+/// Formats seconds since the Unix epoch as a UTC date and time, like `2024-03-09 17:05 UTC`.
+fn format_timestamp(seconds: u64) -> String {
+    let days = (seconds / 86_400) as i64;
+    let (hour, minute) = (seconds / 3600 % 24, seconds / 60 % 60);
+    // Howard Hinnant's `civil_from_days`, working in 400 year eras starting on March 1st
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = era * 400 + year_of_era + i64::from(month <= 2);
+    format!("{year}-{month:02}-{day:02} {hour:02}:{minute:02} UTC")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn save_data() -> SaveData {
+        SaveData {
+            version: SAVE_VERSION,
+            saved_at: 1_700_000_000,
+            play_time_s: 62.5,
+            map: "maps/overworld.tmj".to_string(),
+            player_position: IVec2::new(3, 4),
+            player_facing: IVec2::NEG_Y,
+            flags: HashMap::from([("met_guide".to_string(), FlagValue::Bool(true))]),
+            party: vec!["hero".to_string(), "guide".to_string()],
+            inventory: HashMap::from([("potion".to_string(), 3)]),
+        }
+    }
+
+    #[test]
+    fn saves_round_trip() {
+        let data = save_data();
+        let contents = ron::ser::to_string_pretty(&data, default()).unwrap();
+        let loaded = parse_save(&contents).unwrap();
+        assert_eq!(loaded.version, SAVE_VERSION);
+        assert_eq!(loaded.map, data.map);
+        assert_eq!(loaded.player_position, data.player_position);
+        assert_eq!(loaded.player_facing, data.player_facing);
+        assert_eq!(loaded.flags, data.flags);
+        assert_eq!(loaded.party, data.party);
+        assert_eq!(loaded.inventory, data.inventory);
+    }
+
+    #[test]
+    fn version_1_saves_load_with_an_empty_party_and_inventory() {
+        let contents = r#"(
+            version: 1,
+            saved_at: 1700000000,
+            play_time_s: 10.0,
+            map: "maps/overworld.tmj",
+            player_position: (3, 4),
+            player_facing: (0, -1),
+            flags: {},
+        )"#;
+        let loaded = parse_save(contents).unwrap();
+        assert!(loaded.party.is_empty());
+        assert!(loaded.inventory.is_empty());
+    }
+
+    #[test]
+    fn newer_saves_are_rejected() {
+        let mut data = save_data();
+        data.version = SAVE_VERSION + 1;
+        let contents = ron::ser::to_string_pretty(&data, default()).unwrap();
+        assert!(matches!(
+            parse_save(&contents),
+            Err(SaveError::TooNew(version)) if version == SAVE_VERSION + 1
+        ));
+    }
+}
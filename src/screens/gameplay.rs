@@ -1,25 +1,41 @@
 //! The screen state for the main gameplay.
 
-use bevy::{input::common_conditions::input_just_pressed, prelude::*, ui::Val::*};
+use bevy::{prelude::*, ui::Val::*};
 
-use crate::{Pause, demo::level::spawn_level, menus::Menu, screens::Screen};
+use crate::{
+    Pause,
+    demo::level::spawn_level,
+    game_flags::reset_game_flags,
+    input::{Action, action_just_pressed},
+    menus::Menu,
+    screens::Screen,
+};
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_systems(OnEnter(Screen::Gameplay), spawn_level);
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        spawn_level.after(reset_game_flags),
+    );
 
-    // Toggle pause on key press.
+    // Toggle pause on input.
     app.add_systems(
         Update,
         (
             (pause, spawn_pause_overlay, open_pause_menu).run_if(
+                in_state(Screen::Gameplay).and(in_state(Menu::None)).and(
+                    action_just_pressed(Action::Pause).or(action_just_pressed(Action::Cancel)),
+                ),
+            ),
+            // Read back earlier dialogue, with the game paused in the meantime.
+            (pause, spawn_pause_overlay, open_backlog).run_if(
                 in_state(Screen::Gameplay)
                     .and(in_state(Menu::None))
-                    .and(input_just_pressed(KeyCode::KeyP).or(input_just_pressed(KeyCode::Escape))),
+                    .and(action_just_pressed(Action::Menu)),
             ),
             close_menu.run_if(
                 in_state(Screen::Gameplay)
                     .and(not(in_state(Menu::None)))
-                    .and(input_just_pressed(KeyCode::KeyP)),
+                    .and(action_just_pressed(Action::Pause)),
             ),
         ),
     );
@@ -56,6 +72,10 @@ fn open_pause_menu(mut next_menu: ResMut<NextState<Menu>>) {
     next_menu.set(Menu::Pause);
 }
 
+fn open_backlog(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Backlog);
+}
+
 fn close_menu(mut next_menu: ResMut<NextState<Menu>>) {
     next_menu.set(Menu::None);
 }
@@ -2,11 +2,45 @@
 
 use bevy::prelude::*;
 
-use crate::{menus::Menu, screens::Screen};
+use crate::{asset_tracking::LoadResource, audio::PlayMusic, menus::Menu, screens::Screen};
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_systems(OnEnter(Screen::Title), open_main_menu);
+    app.register_type::<TitleAssets>();
+    app.load_resource::<TitleAssets>();
+
+    app.add_systems(
+        OnEnter(Screen::Title),
+        (
+            open_main_menu,
+            play_title_music.run_if(resource_exists::<TitleAssets>),
+        ),
+    );
     app.add_systems(OnExit(Screen::Title), close_menu);
+    // The music may still be loading the first time the title screen appears.
+    app.add_systems(
+        Update,
+        play_title_music.run_if(in_state(Screen::Title).and(resource_added::<TitleAssets>)),
+    );
+}
+
+#[derive(Resource, Asset, Clone, Reflect)]
+#[reflect(Resource)]
+struct TitleAssets {
+    #[dependency]
+    music: Handle<AudioSource>,
+}
+
+impl FromWorld for TitleAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            music: assets.load("audio/music/title.wav"),
+        }
+    }
+}
+
+fn play_title_music(title_assets: Res<TitleAssets>, mut music_events: EventWriter<PlayMusic>) {
+    music_events.write(PlayMusic::track(title_assets.music.clone()));
 }
 
 fn open_main_menu(mut next_menu: ResMut<NextState<Menu>>) {
@@ -0,0 +1,123 @@
+//! The player's settings, kept in a config file between sessions.
+
+use bevy::{
+    audio::Volume,
+    prelude::*,
+    state::state::StateTransitionSteps,
+    window::{PrimaryWindow, WindowMode},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audio::{MusicVolume, SoundEffectVolume},
+    input::InputBindings,
+    storage::{self, StorageDir},
+    text_boxes::{DialoguePlayback, TextSpeed},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Settings>();
+
+    // The first screen is entered before `Startup` runs, so load and apply the settings in the
+    // state transition that enters it. Later changes are applied in the next transition.
+    app.add_systems(
+        StateTransition,
+        (
+            load_settings.run_if(not(resource_exists::<Settings>)),
+            apply_settings.run_if(resource_exists_and_changed::<Settings>),
+        )
+            .chain()
+            .before(StateTransitionSteps::DependentTransitions),
+    );
+    app.add_systems(
+        Update,
+        save_settings.run_if(resource_changed::<Settings>.and(not(resource_added::<Settings>))),
+    );
+}
+
+const SETTINGS_FILE: &str = "settings.ron";
+
+/// Everything changed in the settings menu. Fields missing from the config file keep their
+/// defaults, so settings added later don't throw out the old file.
+#[derive(Resource, Reflect, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct Settings {
+    /// Linear, where 1 is full volume. So are the other volumes, which scale this one.
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sound_effect_volume: f32,
+    pub text_speed: TextSpeed,
+    pub dialogue_playback: DialoguePlayback,
+    pub fullscreen: bool,
+    pub bindings: InputBindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.,
+            music_volume: 1.,
+            sound_effect_volume: 1.,
+            text_speed: default(),
+            dialogue_playback: default(),
+            fullscreen: false,
+            bindings: default(),
+        }
+    }
+}
+
+fn load_settings(mut commands: Commands) {
+    let settings = match storage::read(StorageDir::Config, SETTINGS_FILE) {
+        Ok(Some(contents)) => ron::from_str(&contents).unwrap_or_else(|error| {
+            warn!("Could not parse settings, using the defaults: {error}");
+            Settings::default()
+        }),
+        Ok(None) => Settings::default(),
+        Err(error) => {
+            warn!("Could not read settings, using the defaults: {error}");
+            Settings::default()
+        }
+    };
+    commands.insert_resource(settings);
+}
+
+fn apply_settings(
+    settings: Res<Settings>,
+    mut global_volume: ResMut<GlobalVolume>,
+    mut music_volume: ResMut<MusicVolume>,
+    mut sound_effect_volume: ResMut<SoundEffectVolume>,
+    mut text_speed: ResMut<TextSpeed>,
+    mut playback: ResMut<DialoguePlayback>,
+    mut bindings: ResMut<InputBindings>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    global_volume.volume = Volume::Linear(settings.master_volume);
+    music_volume.0 = Volume::Linear(settings.music_volume);
+    sound_effect_volume.0 = Volume::Linear(settings.sound_effect_volume);
+    *text_speed = settings.text_speed;
+    *playback = settings.dialogue_playback;
+    *bindings = settings.bindings.clone();
+    let mode = if settings.fullscreen {
+        WindowMode::BorderlessFullscreen(MonitorSelection::Current)
+    } else {
+        WindowMode::Windowed
+    };
+    for mut window in &mut window_query {
+        if window.mode != mode {
+            window.mode = mode;
+        }
+    }
+}
+
+fn save_settings(settings: Res<Settings>) {
+    let result = ron::ser::to_string_pretty(&*settings, default())
+        .map_err(|error| error.to_string())
+        .and_then(|contents| {
+            storage::write(StorageDir::Config, SETTINGS_FILE, &contents)
+                .map_err(|error| error.to_string())
+        });
+    if let Err(error) = result {
+        warn!("Could not save settings: {error}");
+    }
+}
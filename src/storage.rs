@@ -0,0 +1,73 @@
+//! Files the game keeps between sessions: in the user's data or config directory on native, and
+//! in LocalStorage on the web.
+
+use std::io;
+
+#[cfg(not(target_family = "wasm"))]
+use std::{fs, path::PathBuf};
+
+/// Which directory a file is kept in on native. The web keeps everything in LocalStorage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageDir {
+    /// For the player's progress, like saves.
+    Data,
+    /// For preferences, like settings.
+    Config,
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn file_path(dir: StorageDir, name: &str) -> PathBuf {
+    let dir = match dir {
+        StorageDir::Data => dirs::data_dir(),
+        StorageDir::Config => dirs::config_dir(),
+    };
+    // fall back to the working directory if the platform doesn't have the directory
+    dir.map(|dir| dir.join("rpgshell"))
+        .unwrap_or_default()
+        .join(name)
+}
+
+/// Reads the file called `name`, or `None` if it hasn't been written yet.
+#[cfg(not(target_family = "wasm"))]
+pub fn read(dir: StorageDir, name: &str) -> io::Result<Option<String>> {
+    match fs::read_to_string(file_path(dir, name)) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+pub fn write(dir: StorageDir, name: &str, contents: &str) -> io::Result<()> {
+    let path = file_path(dir, name);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    // write next to the old file and swap it in, so a failed write doesn't lose it
+    let mut temporary_path = path.clone().into_os_string();
+    temporary_path.push(".tmp");
+    fs::write(&temporary_path, contents)?;
+    fs::rename(temporary_path, path)
+}
+
+#[cfg(target_family = "wasm")]
+fn local_storage() -> io::Result<web_sys::Storage> {
+    web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .ok_or_else(|| io::Error::other("LocalStorage is unavailable"))
+}
+
+/// Reads the file called `name`, or `None` if it hasn't been written yet.
+#[cfg(target_family = "wasm")]
+pub fn read(_dir: StorageDir, name: &str) -> io::Result<Option<String>> {
+    local_storage()?
+        .get_item(&format!("rpgshell/{name}"))
+        .map_err(|_| io::Error::other("could not read from LocalStorage"))
+}
+
+#[cfg(target_family = "wasm")]
+pub fn write(_dir: StorageDir, name: &str, contents: &str) -> io::Result<()> {
+    local_storage()?
+        .set_item(&format!("rpgshell/{name}"), contents)
+        .map_err(|_| io::Error::other("could not write to LocalStorage"))
+}
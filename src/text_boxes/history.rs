@@ -0,0 +1,55 @@
+//! A record of every line shown in a text box, so players can read back anything they missed.
+
+use bevy::prelude::*;
+
+use crate::{dialogue::DialogueLine, screens::Screen};
+
+use super::markup::MarkupText;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<DialogueHistory>();
+    app.init_resource::<DialogueHistory>();
+    app.add_systems(OnEnter(Screen::Gameplay), clear_dialogue_history);
+}
+
+/// Every line shown by any [`TextBox`](super::TextBox), oldest first.
+#[derive(Resource, Reflect, Debug, Default)]
+#[reflect(Resource)]
+pub struct DialogueHistory {
+    pub entries: Vec<DialogueHistoryEntry>,
+}
+
+#[derive(Reflect, Debug, Clone)]
+pub struct DialogueHistoryEntry {
+    pub speaker: Option<String>,
+    /// The line with its markup taken out.
+    pub text: String,
+    /// How long had been played, as counted by [`PlayTime`](crate::save::PlayTime), when the
+    /// line was shown.
+    pub time_s: f32,
+}
+
+/// Older lines are dropped past this point.
+const MAX_HISTORY_ENTRIES: usize = 200;
+
+impl DialogueHistory {
+    pub fn record(&mut self, line: &DialogueLine, time_s: f32) {
+        // rows are only split up to fit the text box
+        let text = MarkupText::parse(&line.text)
+            .plain_text()
+            .replace('\n', " ");
+        self.entries.push(DialogueHistoryEntry {
+            speaker: line.speaker.clone(),
+            text,
+            time_s,
+        });
+        if self.entries.len() > MAX_HISTORY_ENTRIES {
+            let excess = self.entries.len() - MAX_HISTORY_ENTRIES;
+            self.entries.drain(..excess);
+        }
+    }
+}
+
+fn clear_dialogue_history(mut history: ResMut<DialogueHistory>) {
+    history.entries.clear();
+}
@@ -0,0 +1,452 @@
+//! Inline markup for dialogue text.
+//!
+//! Supported tags:
+//! - `[color=gold]...[/color]`: a named color or a hex code like `#ffd700`.
+//! - `[wave]...[/wave]`: characters bob up and down.
+//! - `[shake]...[/shake]`: characters jitter in place.
+//! - `[pause=0.5]`: the typewriter reveal stops for this many seconds.
+//!
+//! Anything in square brackets that isn't one of these is shown as-is.
+
+use bevy::{color::palettes::css, prelude::*};
+
+/// An animation applied to each character of a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum TextEffect {
+    Wave,
+    Shake,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Reflect)]
+pub struct MarkupStyle {
+    pub color: Option<Color>,
+    pub effect: Option<TextEffect>,
+}
+
+/// A stretch of text that shares a single style.
+#[derive(Debug, Clone, PartialEq, Reflect)]
+pub struct MarkupRun {
+    pub text: String,
+    pub style: MarkupStyle,
+}
+
+/// Dialogue text with its markup parsed out.
+#[derive(Debug, Clone, PartialEq, Default, Reflect)]
+pub struct MarkupText {
+    pub runs: Vec<MarkupRun>,
+    /// Pauses in the reveal as `(character index, seconds)`, in order. The pause happens right
+    /// before the character at that index appears.
+    pub pauses: Vec<(usize, f32)>,
+}
+
+impl MarkupText {
+    pub fn parse(source: &str) -> Self {
+        let mut text = Self::default();
+        let mut colors: Vec<Color> = Vec::new();
+        let mut effects: Vec<TextEffect> = Vec::new();
+        let mut char_count = 0;
+
+        for token in tokenize(source) {
+            match token {
+                Token::Char(c) => {
+                    let style = MarkupStyle {
+                        color: colors.last().copied(),
+                        effect: effects.last().copied(),
+                    };
+                    match text.runs.last_mut() {
+                        Some(run) if run.style == style => run.text.push(c),
+                        _ => text.runs.push(MarkupRun {
+                            text: c.to_string(),
+                            style,
+                        }),
+                    }
+                    char_count += 1;
+                }
+                Token::Tag { tag, .. } => match tag {
+                    Tag::Color(color) => colors.push(color),
+                    Tag::Effect(effect) => effects.push(effect),
+                    Tag::CloseColor => {
+                        colors.pop();
+                    }
+                    Tag::CloseEffect(effect) => {
+                        if let Some(index) = effects.iter().rposition(|open| *open == effect) {
+                            effects.remove(index);
+                        }
+                    }
+                    Tag::Pause(seconds) => text.pauses.push((char_count, seconds)),
+                },
+            }
+        }
+        text
+    }
+
+    pub fn char_count(&self) -> usize {
+        self.runs.iter().map(|run| run.text.chars().count()).sum()
+    }
+
+    /// The text without any markup.
+    pub fn plain_text(&self) -> String {
+        self.runs.iter().map(|run| run.text.as_str()).collect()
+    }
+}
+
+/// Word wraps `source` to rows of at most `max_chars` visible characters and splits the rows
+/// into pages of at most `max_rows`. Tags don't take up any room, and any tags still open at
+/// the end of a page are opened again at the start of the next one, so every page can be
+/// parsed on its own.
+pub fn paginate(source: &str, max_chars: usize, max_rows: usize) -> Vec<String> {
+    let max_chars = max_chars.max(1);
+    let mut rows: Vec<Vec<Token>> = Vec::new();
+    let mut row = RowBuilder::default();
+    let mut word = RowBuilder::default();
+
+    for token in tokenize(source) {
+        match token {
+            Token::Char('\n') => {
+                row.push_word(&mut word, &mut rows, max_chars);
+                rows.push(row.take());
+            }
+            Token::Char(c) if c.is_whitespace() => {
+                row.push_word(&mut word, &mut rows, max_chars);
+            }
+            Token::Char(_) => {
+                word.len += 1;
+                word.tokens.push(token);
+            }
+            Token::Tag { .. } => word.tokens.push(token),
+        }
+    }
+    row.push_word(&mut word, &mut rows, max_chars);
+    rows.push(row.take());
+
+    let mut open_tags: Vec<(Tag, String)> = Vec::new();
+    rows.chunks(max_rows.max(1))
+        .map(|page_rows| {
+            let mut page: String = open_tags
+                .iter()
+                .map(|(_, source)| source.as_str())
+                .collect();
+            for (i, row) in page_rows.iter().enumerate() {
+                if i > 0 {
+                    page.push('\n');
+                }
+                for token in row {
+                    match token {
+                        Token::Char(c) => page.push(*c),
+                        Token::Tag { tag, source } => {
+                            track_open_tag(&mut open_tags, *tag, source);
+                            page.push_str(source);
+                        }
+                    }
+                }
+            }
+            page
+        })
+        .collect()
+}
+
+fn track_open_tag(open_tags: &mut Vec<(Tag, String)>, tag: Tag, source: &str) {
+    let closes = |open: &Tag| match (tag, open) {
+        (Tag::CloseColor, Tag::Color(_)) => true,
+        (Tag::CloseEffect(effect), Tag::Effect(open_effect)) => effect == *open_effect,
+        _ => false,
+    };
+    match tag {
+        Tag::Color(_) | Tag::Effect(_) => open_tags.push((tag, source.to_string())),
+        Tag::CloseColor | Tag::CloseEffect(_) => {
+            if let Some(index) = open_tags.iter().rposition(|(open, _)| closes(open)) {
+                open_tags.remove(index);
+            }
+        }
+        Tag::Pause(_) => {}
+    }
+}
+
+#[derive(Default)]
+struct RowBuilder {
+    tokens: Vec<Token>,
+    /// Visible characters, not counting tags.
+    len: usize,
+}
+
+impl RowBuilder {
+    fn take(&mut self) -> Vec<Token> {
+        self.len = 0;
+        std::mem::take(&mut self.tokens)
+    }
+
+    /// Moves `word` onto the end of this row, starting new rows as needed.
+    fn push_word(&mut self, word: &mut RowBuilder, rows: &mut Vec<Vec<Token>>, max_chars: usize) {
+        if word.len > max_chars {
+            // too long for a row on its own, so break it up
+            if self.len > 0 {
+                rows.push(self.take());
+            }
+            for token in word.take() {
+                if matches!(token, Token::Char(_)) {
+                    if self.len == max_chars {
+                        rows.push(self.take());
+                    }
+                    self.len += 1;
+                }
+                self.tokens.push(token);
+            }
+            return;
+        }
+        if word.len > 0 && self.len > 0 {
+            if self.len + 1 + word.len > max_chars {
+                rows.push(self.take());
+            } else {
+                self.tokens.push(Token::Char(' '));
+                self.len += 1;
+            }
+        }
+        self.len += word.len;
+        self.tokens.extend(word.take());
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Char(char),
+    Tag { tag: Tag, source: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Tag {
+    Color(Color),
+    CloseColor,
+    Effect(TextEffect),
+    CloseEffect(TextEffect),
+    Pause(f32),
+}
+
+fn tokenize(source: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+    while let Some(c) = rest.chars().next() {
+        if c == '['
+            && let Some(end) = rest.find(']')
+            && let Some(tag) = parse_tag(&rest[1..end])
+        {
+            tokens.push(Token::Tag {
+                tag,
+                source: rest[..=end].to_string(),
+            });
+            rest = &rest[end + 1..];
+            continue;
+        }
+        tokens.push(Token::Char(c));
+        rest = &rest[c.len_utf8()..];
+    }
+    tokens
+}
+
+fn parse_tag(tag: &str) -> Option<Tag> {
+    let (name, value) = match tag.split_once('=') {
+        Some((name, value)) => (name.trim(), Some(value.trim())),
+        None => (tag.trim(), None),
+    };
+    match (name, value) {
+        ("color", Some(value)) => named_color(value).map(Tag::Color),
+        ("/color", None) => Some(Tag::CloseColor),
+        ("wave", None) => Some(Tag::Effect(TextEffect::Wave)),
+        ("/wave", None) => Some(Tag::CloseEffect(TextEffect::Wave)),
+        ("shake", None) => Some(Tag::Effect(TextEffect::Shake)),
+        ("/shake", None) => Some(Tag::CloseEffect(TextEffect::Shake)),
+        ("pause", Some(value)) => value
+            .parse()
+            .ok()
+            .filter(|seconds: &f32| seconds.is_finite() && *seconds >= 0.)
+            .map(Tag::Pause),
+        _ => None,
+    }
+}
+
+fn named_color(name: &str) -> Option<Color> {
+    let color = match name.to_ascii_lowercase().as_str() {
+        "white" => css::GHOST_WHITE,
+        "black" => css::BLACK,
+        "gray" | "grey" => css::GRAY,
+        "red" => css::CRIMSON,
+        "orange" => css::ORANGE,
+        "gold" => css::GOLD,
+        "yellow" => css::YELLOW,
+        "green" => css::LIME,
+        "cyan" => css::AQUA,
+        "blue" => css::DODGER_BLUE,
+        "purple" => css::MEDIUM_PURPLE,
+        "pink" => css::HOT_PINK,
+        _ => return Srgba::hex(name).ok().map(Color::from),
+    };
+    Some(color.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn style(color: Option<Srgba>, effect: Option<TextEffect>) -> MarkupStyle {
+        MarkupStyle {
+            color: color.map(Color::from),
+            effect,
+        }
+    }
+
+    fn runs(source: &str) -> Vec<(String, MarkupStyle)> {
+        MarkupText::parse(source)
+            .runs
+            .into_iter()
+            .map(|run| (run.text, run.style))
+            .collect()
+    }
+
+    fn plain(text: &MarkupText) -> String {
+        text.runs.iter().map(|run| run.text.as_str()).collect()
+    }
+
+    #[test]
+    fn plain_text_is_one_unstyled_run() {
+        let text = MarkupText::parse("Hello there");
+        assert_eq!(text.runs.len(), 1);
+        assert_eq!(text.runs[0].style, MarkupStyle::default());
+        assert_eq!(text.char_count(), 11);
+        assert!(text.pauses.is_empty());
+    }
+
+    #[test]
+    fn color_tags_style_the_text_between_them() {
+        assert_eq!(
+            runs("a[color=gold]b[/color]c"),
+            vec![
+                ("a".to_string(), style(None, None)),
+                ("b".to_string(), style(Some(css::GOLD), None)),
+                ("c".to_string(), style(None, None)),
+            ]
+        );
+        assert_eq!(
+            runs("[color=#ff0000]x"),
+            vec![(
+                "x".to_string(),
+                style(Some(Srgba::hex("#ff0000").unwrap()), None)
+            )]
+        );
+    }
+
+    #[test]
+    fn nested_tags_combine_and_close_independently() {
+        assert_eq!(
+            runs("[color=red][wave]ab[/color]c[/wave]d"),
+            vec![
+                (
+                    "ab".to_string(),
+                    style(Some(css::CRIMSON), Some(TextEffect::Wave))
+                ),
+                ("c".to_string(), style(None, Some(TextEffect::Wave))),
+                ("d".to_string(), style(None, None)),
+            ]
+        );
+        // closing the outer effect first leaves the inner one going
+        assert_eq!(
+            runs("[wave][shake]a[/wave]b[/shake]c"),
+            vec![
+                ("ab".to_string(), style(None, Some(TextEffect::Shake))),
+                ("c".to_string(), style(None, None)),
+            ]
+        );
+    }
+
+    #[test]
+    fn unclosed_tags_run_to_the_end() {
+        assert_eq!(
+            runs("a[shake]bc"),
+            vec![
+                ("a".to_string(), style(None, None)),
+                ("bc".to_string(), style(None, Some(TextEffect::Shake))),
+            ]
+        );
+    }
+
+    #[test]
+    fn stray_closing_tags_are_ignored() {
+        let text = MarkupText::parse("a[/color]b[/wave]c");
+        assert_eq!(plain(&text), "abc");
+        assert_eq!(text.runs.len(), 1);
+    }
+
+    #[test]
+    fn unknown_tags_and_brackets_are_shown_as_is() {
+        for source in [
+            "[bold]x[/bold]",
+            "a [b",
+            "a ] b",
+            "[color=notacolor]x",
+            "[color]x",
+            "[wave=2]x",
+        ] {
+            let text = MarkupText::parse(source);
+            assert_eq!(plain(&text), source);
+            assert_eq!(text.runs.len(), 1, "{source}");
+        }
+    }
+
+    #[test]
+    fn pauses_come_before_the_next_character() {
+        let text = MarkupText::parse("ab[pause=0.5]c[pause = 2]");
+        assert_eq!(plain(&text), "abc");
+        assert_eq!(text.pauses, vec![(2, 0.5), (3, 2.)]);
+    }
+
+    #[test]
+    fn bad_pauses_are_shown_as_is() {
+        for source in [
+            "[pause=]",
+            "[pause=soon]",
+            "[pause=-1]",
+            "[pause=inf]",
+            "[pause=NaN]",
+            "[pause]",
+        ] {
+            let text = MarkupText::parse(source);
+            assert!(text.pauses.is_empty(), "{source}");
+            assert_eq!(plain(&text), source);
+        }
+    }
+
+    #[test]
+    fn paginate_wraps_words_to_rows() {
+        assert_eq!(paginate("one two three", 7, 10), vec!["one two\nthree"]);
+        assert_eq!(paginate("a\nb", 10, 10), vec!["a\nb"]);
+    }
+
+    #[test]
+    fn paginate_splits_rows_into_pages() {
+        assert_eq!(paginate("a b c", 1, 2), vec!["a\nb", "c"]);
+    }
+
+    #[test]
+    fn paginate_breaks_up_words_longer_than_a_row() {
+        assert_eq!(paginate("abcdefgh", 3, 10), vec!["abc\ndef\ngh"]);
+        assert_eq!(paginate("hi abcdefgh", 3, 10), vec!["hi\nabc\ndef\ngh"]);
+    }
+
+    #[test]
+    fn paginate_does_not_count_tags_as_width() {
+        assert_eq!(
+            paginate("[color=red]ab[/color] cd", 5, 5),
+            vec!["[color=red]ab[/color] cd"]
+        );
+    }
+
+    #[test]
+    fn paginate_reopens_tags_on_the_next_page() {
+        let pages = paginate("[wave]a b[/wave] c", 1, 1);
+        assert_eq!(pages, vec!["[wave]a", "[wave]b[/wave]", "c"]);
+        assert_eq!(
+            runs(&pages[1]),
+            vec![("b".to_string(), style(None, Some(TextEffect::Wave)))]
+        );
+        // pauses happen once, so they aren't carried over
+        assert_eq!(paginate("[pause=1]a b", 1, 1), vec!["[pause=1]a", "b"]);
+    }
+}
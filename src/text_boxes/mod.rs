@@ -0,0 +1,1517 @@
+mod history;
+mod markup;
+mod playback;
+mod style;
+
+use bevy::{
+    ecs::spawn::SpawnIter,
+    math::curve::EaseFunction,
+    prelude::*,
+    render::{mesh::Indices, render_asset::RenderAssetUsages, render_resource::PrimitiveTopology},
+    text::{LineBreak, TextBounds},
+};
+use rand::Rng;
+
+use crate::{
+    AppSystems, PausableSystems,
+    audio::{DucksMusic, sound_effect},
+    dialogue::{
+        Conversation, DialogueAssets, DialogueChoice, DialogueLine, DialogueScript, DialogueVoice,
+    },
+    game_flags::GameFlags,
+    input::{Action, ActionInput},
+    save::PlayTime,
+    screens::Screen,
+    theme::prelude::*,
+};
+
+pub use self::history::{DialogueHistory, DialogueHistoryEntry};
+use self::markup::{MarkupText, TextEffect};
+use self::playback::DialogueFastForward;
+pub use self::playback::{DialoguePlayback, TextSpeed};
+pub use self::style::{TextBoxStyle, TextBoxTransition};
+
+/// A box of dialogue. Its lines, indicator and speaker details are spawned as its children, so
+/// several text boxes can be on screen at once, each positioned by its own [`Transform`] and
+/// drawn with its own [`TextBoxStyle`].
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+#[require(Transform, Visibility)]
+pub struct TextBox {
+    /// The pages shown one after another, each its own line.
+    pub lines: Vec<DialogueLine>,
+    /// The lines as written, before they were split into [`Self::lines`].
+    pub source_lines: Vec<DialogueLine>,
+    /// Which of [`Self::source_lines`] each of [`Self::lines`] is a page of.
+    pub page_sources: Vec<usize>,
+    pub current_text_index: usize,
+    pub last_text_index_displayed: Option<usize>,
+    pub time_since_last_text_displayed: f32,
+    pub should_spawn_next_line: bool,
+    pub indicator_visible: bool,
+    /// How long the indicator has been showing, used to advance lines automatically.
+    pub indicator_time_s: f32,
+    pub is_finished: bool,
+    /// How many characters of each line are revealed per second.
+    pub chars_per_second: f32,
+    /// Offered to the player once the last line has been revealed.
+    pub choices: Vec<DialogueChoice>,
+    pub choices_visible: bool,
+    pub selected_choice: usize,
+    pub phase: TextBoxPhase,
+    /// How long the box has been in its current [`TextBoxPhase`].
+    pub phase_time_s: f32,
+}
+
+/// Marks the [`TextBox`] that dialogue input goes to: the most recently opened one that's still
+/// around.
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct FocusedTextBox;
+
+/// Where a [`TextBox`] is in its lifecycle. It plays its [`TextBoxStyle::transition`] while
+/// opening and closing, and is despawned once it has closed.
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextBoxPhase {
+    #[default]
+    Opening,
+    Open,
+    Closing,
+}
+
+impl TextBox {
+    /// Lines that don't fit in a box of this `style` are split into several pages, each shown as
+    /// its own line.
+    pub fn new(lines: Vec<DialogueLine>, style: &TextBoxStyle) -> Self {
+        let (pages, page_sources) = paginate_lines(&lines, style);
+        Self {
+            lines: pages,
+            source_lines: lines,
+            page_sources,
+            current_text_index: 0,
+            last_text_index_displayed: None,
+            time_since_last_text_displayed: 0.0,
+            should_spawn_next_line: false,
+            indicator_visible: false,
+            indicator_time_s: 0.,
+            is_finished: false,
+            chars_per_second: TEXT_REVEAL_CHARS_PER_SECOND,
+            choices: Vec::new(),
+            choices_visible: false,
+            selected_choice: 0,
+            phase: TextBoxPhase::Opening,
+            phase_time_s: 0.,
+        }
+    }
+
+    pub fn from_conversation(conversation: &Conversation, style: &TextBoxStyle) -> Self {
+        Self {
+            choices: conversation.choices.clone(),
+            ..Self::new(conversation.lines.clone(), style)
+        }
+    }
+
+    /// Replace the remaining dialogue with `conversation`, starting from its first line.
+    pub fn start_conversation(&mut self, conversation: &Conversation, style: &TextBoxStyle) {
+        (self.lines, self.page_sources) = paginate_lines(&conversation.lines, style);
+        self.source_lines = conversation.lines.clone();
+        self.choices = conversation.choices.clone();
+        self.current_text_index = 0;
+        self.last_text_index_displayed = None;
+        self.time_since_last_text_displayed = TEXT_TRANSITION_TIME;
+        self.indicator_visible = false;
+        self.choices_visible = false;
+        self.selected_choice = 0;
+    }
+
+    /// Play the close transition and despawn the box afterwards. This happens on its own once
+    /// the dialogue is finished.
+    pub fn close(&mut self) {
+        if self.phase != TextBoxPhase::Closing {
+            self.phase = TextBoxPhase::Closing;
+            self.phase_time_s = 0.;
+        }
+    }
+
+    /// How far open the box is, from 0 (closed) to 1 (fully open).
+    fn openness(&self, style: &TextBoxStyle) -> f32 {
+        let progress = if style.transition_time > 0. {
+            (self.phase_time_s / style.transition_time).min(1.)
+        } else {
+            1.
+        };
+        let openness = match self.phase {
+            TextBoxPhase::Opening => progress,
+            TextBoxPhase::Open => 1.,
+            TextBoxPhase::Closing => 1. - progress,
+        };
+        EaseFunction::SmoothStep.sample_clamped(openness)
+    }
+
+    /// The line as written, if the current page is the first one of it.
+    fn line_starting_on_current_page(&self) -> Option<&DialogueLine> {
+        let index = self.current_text_index;
+        let source = *self.page_sources.get(index)?;
+        if index > 0 && self.page_sources[index - 1] == source {
+            return None;
+        }
+        self.source_lines.get(source)
+    }
+
+    fn is_on_last_line(&self) -> bool {
+        self.current_text_index + 1 >= self.lines.len()
+    }
+}
+
+/// Sent when a [`TextBox`] has finished its open transition.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct TextBoxOpened {
+    pub text_box: Entity,
+}
+
+/// Sent when a [`TextBox`] has finished its close transition and has been despawned.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct TextBoxClosed {
+    pub text_box: Entity,
+}
+
+/// Sent when the last line of a [`TextBox`] has been dismissed by the player. The box closes
+/// right after.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct TextBoxFinished {
+    pub text_box: Entity,
+}
+
+/// Sent when the player picks one of the [`TextBox::choices`].
+#[derive(Event, Debug, Clone)]
+pub struct TextBoxChoiceMade {
+    pub text_box: Entity,
+    pub choice_id: String,
+}
+
+/// The player confirmed one of a [`TextBox`]'s choices, either by clicking it or with the
+/// keyboard.
+#[derive(Event, Debug, Clone, Copy)]
+struct DialogueChoiceConfirmed {
+    text_box: Entity,
+    index: usize,
+}
+
+/// The container for the choice buttons of a [`TextBox`]. Being UI, it can't be one of the
+/// text box's children, so it keeps track of the text box instead.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct TextBoxChoices {
+    pub text_box: Entity,
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct TextBoxChoiceButton {
+    pub text_box: Entity,
+    pub index: usize,
+}
+
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct TextLine;
+
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct TextBoxIndicator;
+
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct TextBoxText {
+    pub is_fully_revealed: bool,
+    pub markup: MarkupText,
+    /// How many characters [`Self::markup`] shows, counted once up front.
+    pub char_count: usize,
+    pub revealed_chars: usize,
+    /// Fractional number of characters revealed so far, advanced by [`Self::chars_per_second`].
+    pub reveal_progress: f32,
+    pub chars_per_second: f32,
+    /// Index into [`MarkupText::pauses`] of the next pause to wait on.
+    pub next_pause: usize,
+    pub pause_remaining_s: f32,
+    /// Blips as the text is revealed, if set.
+    pub voice: Option<DialogueVoice>,
+    /// How many characters had been revealed when [`play_voice_blips`] last checked.
+    pub voiced_chars: usize,
+    pub time_since_blip_s: f32,
+}
+
+impl TextBoxText {
+    pub fn new(markup: MarkupText, chars_per_second: f32) -> Self {
+        Self {
+            is_fully_revealed: false,
+            char_count: markup.char_count(),
+            markup,
+            revealed_chars: 0,
+            reveal_progress: 0.,
+            chars_per_second,
+            next_pause: 0,
+            pause_remaining_s: 0.,
+            voice: None,
+            voiced_chars: 0,
+            time_since_blip_s: VOICE_BLIP_INTERVAL,
+        }
+    }
+
+    /// Skip the rest of the typewriter reveal (including any pauses), the full line is shown on
+    /// the next update.
+    pub fn reveal_all(&mut self) {
+        self.revealed_chars = self.char_count;
+        self.reveal_progress = self.revealed_chars as f32;
+        self.next_pause = self.markup.pauses.len();
+        self.pause_remaining_s = 0.;
+    }
+}
+
+/// A single character drawn on its own so it can be animated by a [`TextEffect`]. Its spot in
+/// the parent [`TextBoxText`] is kept free by a transparent span.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct TextBoxGlyph {
+    pub char_index: usize,
+    pub effect: TextEffect,
+    pub color: Color,
+    pub base_translation: Vec3,
+}
+
+/// A part of a text box's frame.
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct TextBoxMesh {
+    /// The alpha once the box is fully open.
+    pub visible_alpha: f32,
+    pub with_cutout: bool,
+}
+
+impl TextBoxMesh {
+    pub fn new(visible_alpha: f32, with_cutout: bool) -> Self {
+        Self {
+            visible_alpha,
+            with_cutout,
+        }
+    }
+}
+
+/// The meshes of a single text box, built from its [`TextBoxStyle`] when it's spawned. Fades and
+/// color changes are applied to these in place instead of adding new meshes.
+#[derive(Component, Clone)]
+pub struct TextBoxMeshes {
+    pub frame: Handle<Mesh>,
+    pub frame_shadow: Handle<Mesh>,
+    pub indicator: Handle<Mesh>,
+    pub indicator_material: Handle<ColorMaterial>,
+}
+
+impl TextBoxMeshes {
+    pub fn new(
+        style: &TextBoxStyle,
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<ColorMaterial>,
+    ) -> Self {
+        Self {
+            frame: meshes.add(get_text_box_mesh(style, true)),
+            frame_shadow: meshes.add(get_text_box_mesh(style, false)),
+            indicator: meshes.add(get_colored_triangle_mesh(style, 0.)),
+            indicator_material: materials.add(ColorMaterial::default()),
+        }
+    }
+}
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins((history::plugin, playback::plugin, style::plugin));
+
+    app.register_type::<TextBox>();
+    app.register_type::<TextBoxPhase>();
+    app.register_type::<FocusedTextBox>();
+    app.register_type::<TextBoxMesh>();
+    app.register_type::<TextBoxText>();
+    app.register_type::<TextBoxGlyph>();
+    app.register_type::<TextBoxIndicator>();
+    app.register_type::<TextBoxChoices>();
+    app.register_type::<TextBoxChoiceButton>();
+    app.add_event::<TextBoxOpened>();
+    app.add_event::<TextBoxClosed>();
+    app.add_event::<TextBoxFinished>();
+    app.add_event::<TextBoxChoiceMade>();
+    app.add_event::<DialogueChoiceConfirmed>();
+
+    app.add_systems(
+        Update,
+        (
+            focus_newest_text_box,
+            advance_text_box
+                .in_set(PausableSystems)
+                .run_if(confirm_just_pressed),
+        )
+            .chain()
+            .in_set(AppSystems::RecordInput),
+    );
+    // Everything here is timed with virtual `Time`, which stands still while the game is paused.
+    app.add_systems(
+        Update,
+        (
+            animate_text_box_transitions,
+            (
+                reveal_text_box_text,
+                (animate_text_box_glyphs, play_voice_blips),
+            )
+                .chain(),
+            animate_text_box_indicator,
+            (spawn_text_lines, position_text_box_choices).chain(),
+        )
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems),
+    );
+
+    app.add_systems(
+        Update,
+        (
+            (
+                navigate_choices.run_if(choice_navigation_just_pressed),
+                confirm_selected_choice.run_if(confirm_key_just_pressed),
+            )
+                .after(focus_newest_text_box)
+                .in_set(AppSystems::RecordInput),
+            (apply_dialogue_choice, highlight_selected_choice)
+                .chain()
+                .in_set(AppSystems::Update),
+        )
+            .in_set(PausableSystems),
+    );
+    app.add_observer(select_hovered_choice);
+}
+
+const TEXT_TRANSITION_TIME: f32 = 0.75;
+fn spawn_text_lines(
+    mut commands: Commands,
+    mut textbox_query: Query<(Entity, &mut TextBox, &TextBoxStyle, &TextBoxMeshes)>,
+    children_query: Query<&Children>,
+    text_query: Query<&TextBoxText>,
+    fast_forward: Res<DialogueFastForward>,
+    mut history: ResMut<DialogueHistory>,
+    play_time: Res<PlayTime>,
+    time: Res<Time>,
+) {
+    for (entity, mut textbox, style, text_box_meshes) in &mut textbox_query {
+        if textbox.is_finished || textbox.phase != TextBoxPhase::Open {
+            continue;
+        }
+
+        if textbox.last_text_index_displayed != Some(textbox.current_text_index) {
+            if textbox.time_since_last_text_displayed >= TEXT_TRANSITION_TIME {
+                // spawn the current text line, along with who's saying it
+                let Some(line) = textbox.lines.get(textbox.current_text_index) else {
+                    warn!("Text box ran out of lines to show, closing it");
+                    textbox.close();
+                    continue;
+                };
+                commands
+                    .spawn((
+                        text_line(
+                            &line.text,
+                            style,
+                            line.portrait_image.is_some(),
+                            line.voice.clone(),
+                            textbox.chars_per_second,
+                        ),
+                        ChildOf(entity),
+                    ))
+                    .with_children(|parent| {
+                        if let Some(speaker) = &line.speaker {
+                            parent.spawn(speaker_name_plate(speaker.clone(), style));
+                        }
+                        if let Some(portrait) = &line.portrait_image {
+                            parent.spawn(speaker_portrait(portrait.clone(), style));
+                        }
+                    });
+                // pages after the first are part of a line that's already recorded
+                if let Some(source_line) = textbox.line_starting_on_current_page() {
+                    history.record(source_line, play_time.0.as_secs_f32());
+                }
+                textbox.last_text_index_displayed = Some(textbox.current_text_index);
+                textbox.time_since_last_text_displayed = 0.;
+            } else {
+                textbox.time_since_last_text_displayed += time.delta_secs() * fast_forward.speed();
+            }
+        } else if !textbox.indicator_visible
+            && !textbox.choices_visible
+            && line_fully_revealed(entity, &children_query, &text_query)
+        {
+            if textbox.is_on_last_line() && !textbox.choices.is_empty() {
+                // offer the choices instead of the indicator at the end of the conversation
+                commands.spawn(text_box_choices(entity, style.size.x, &textbox.choices));
+                textbox.choices_visible = true;
+                textbox.selected_choice = 0;
+            } else {
+                // spawn indicator once the whole line has been typed out
+                commands.spawn((
+                    text_box_next_indicator(text_box_meshes, style, time.elapsed_secs()),
+                    ChildOf(entity),
+                ));
+                textbox.indicator_visible = true;
+                textbox.indicator_time_s = 0.;
+            }
+        }
+    }
+}
+
+/// Whether the line currently shown in `text_box` has been typed out completely.
+fn line_fully_revealed(
+    text_box: Entity,
+    children_query: &Query<&Children>,
+    text_query: &Query<&TextBoxText>,
+) -> bool {
+    let mut texts = text_query
+        .iter_many(children_query.iter_descendants(text_box))
+        .peekable();
+    texts.peek().is_some() && texts.all(|text| text.is_fully_revealed)
+}
+
+/// Whether the player pressed any of the inputs that confirm/advance dialogue this frame.
+fn confirm_just_pressed(input: ActionInput, mouse: Res<ButtonInput<MouseButton>>) -> bool {
+    input.just_pressed(Action::Confirm) || mouse.just_pressed(MouseButton::Left)
+}
+
+/// Like [`confirm_just_pressed`], minus the mouse. Clicks on choices are handled by the buttons
+/// themselves.
+fn confirm_key_just_pressed(input: ActionInput) -> bool {
+    input.just_pressed(Action::Confirm)
+}
+
+fn choice_navigation_just_pressed(input: ActionInput) -> bool {
+    input.just_pressed(Action::MoveUp) || input.just_pressed(Action::MoveDown)
+}
+
+/// Whether any of the inputs that confirm/advance dialogue are held down.
+fn confirm_held(input: &ActionInput, mouse: &ButtonInput<MouseButton>) -> bool {
+    input.pressed(Action::Confirm) || mouse.pressed(MouseButton::Left)
+}
+
+/// Moves [`FocusedTextBox`] to the newest [`TextBox`], or back to the one opened before it once
+/// the newest is gone.
+fn focus_newest_text_box(
+    mut commands: Commands,
+    mut opened: Local<Vec<Entity>>,
+    added_query: Query<Entity, Added<TextBox>>,
+    textbox_query: Query<(), With<TextBox>>,
+    focused_query: Query<Entity, With<FocusedTextBox>>,
+) {
+    opened.retain(|&entity| textbox_query.contains(entity));
+    opened.extend(&added_query);
+    let newest = opened.last().copied();
+    for entity in &focused_query {
+        if Some(entity) != newest {
+            commands.entity(entity).remove::<FocusedTextBox>();
+        }
+    }
+    if let Some(newest) = newest
+        && !focused_query.contains(newest)
+    {
+        commands.entity(newest).insert(FocusedTextBox);
+    }
+}
+
+/// Completes the line if it's still being typed out. Otherwise dismisses the current line once
+/// its indicator is showing, and either queues up the next line or reports that the [`TextBox`]
+/// has run out of lines.
+fn advance_text_box(
+    mut commands: Commands,
+    mut textbox_query: Query<(Entity, &mut TextBox, Option<&Children>), With<FocusedTextBox>>,
+    children_query: Query<&Children>,
+    mut text_query: Query<&mut TextBoxText>,
+    line_query: Query<(), Or<(With<TextLine>, With<TextBoxIndicator>)>>,
+    mut finished_events: EventWriter<TextBoxFinished>,
+) {
+    for (entity, mut textbox, children) in &mut textbox_query {
+        if textbox.is_finished
+            || textbox.phase != TextBoxPhase::Open
+            || textbox.choices_visible
+            || textbox.last_text_index_displayed.is_none()
+        {
+            continue;
+        }
+        if !textbox.indicator_visible {
+            let mut texts = text_query.iter_many_mut(children_query.iter_descendants(entity));
+            while let Some(mut text) = texts.fetch_next() {
+                text.reveal_all();
+            }
+            continue;
+        }
+
+        dismiss_line(
+            &mut commands,
+            entity,
+            &mut textbox,
+            children,
+            &line_query,
+            &mut finished_events,
+        );
+    }
+}
+
+/// Takes down the current line of a [`TextBox`] along with its indicator.
+fn dismiss_line(
+    commands: &mut Commands,
+    entity: Entity,
+    textbox: &mut TextBox,
+    children: Option<&Children>,
+    line_query: &Query<(), Or<(With<TextLine>, With<TextBoxIndicator>)>>,
+    finished_events: &mut EventWriter<TextBoxFinished>,
+) {
+    for child in children.into_iter().flatten() {
+        if line_query.contains(*child) {
+            commands.entity(*child).despawn();
+        }
+    }
+    textbox.indicator_visible = false;
+
+    if !textbox.is_on_last_line() {
+        // show the next line straight away, it starts out blank and gets typed out
+        textbox.current_text_index += 1;
+        textbox.time_since_last_text_displayed = TEXT_TRANSITION_TIME;
+    } else {
+        textbox.is_finished = true;
+        finished_events.write(TextBoxFinished { text_box: entity });
+    }
+}
+
+fn navigate_choices(
+    input: ActionInput,
+    mut textbox_query: Query<&mut TextBox, With<FocusedTextBox>>,
+) {
+    let up = input.just_pressed(Action::MoveUp);
+    for mut textbox in &mut textbox_query {
+        if !textbox.choices_visible {
+            continue;
+        }
+        let choice_count = textbox.choices.len();
+        // wrap around at either end of the list
+        textbox.selected_choice = if up {
+            (textbox.selected_choice + choice_count - 1) % choice_count
+        } else {
+            (textbox.selected_choice + 1) % choice_count
+        };
+    }
+}
+
+fn confirm_selected_choice(
+    textbox_query: Query<(Entity, &TextBox), With<FocusedTextBox>>,
+    mut confirmed_events: EventWriter<DialogueChoiceConfirmed>,
+) {
+    for (entity, textbox) in &textbox_query {
+        if textbox.choices_visible {
+            confirmed_events.write(DialogueChoiceConfirmed {
+                text_box: entity,
+                index: textbox.selected_choice,
+            });
+        }
+    }
+}
+
+/// Keep the keyboard selection in sync with the mouse, so only one choice looks selected.
+fn select_hovered_choice(
+    trigger: Trigger<Pointer<Over>>,
+    button_query: Query<&TextBoxChoiceButton>,
+    mut textbox_query: Query<&mut TextBox>,
+) {
+    let Ok(button) = button_query.get(trigger.target()) else {
+        return;
+    };
+    if let Ok(mut textbox) = textbox_query.get_mut(button.text_box) {
+        textbox.selected_choice = button.index;
+    }
+}
+
+/// Reports the confirmed choice, then jumps to the conversation it leads to or ends the
+/// dialogue.
+fn apply_dialogue_choice(
+    mut commands: Commands,
+    mut confirmed_events: EventReader<DialogueChoiceConfirmed>,
+    mut textbox_query: Query<(&mut TextBox, &TextBoxStyle, Option<&Children>)>,
+    line_query: Query<(), With<TextLine>>,
+    choices_query: Query<(Entity, &TextBoxChoices)>,
+    dialogue_assets: Res<DialogueAssets>,
+    dialogue_scripts: Res<Assets<DialogueScript>>,
+    mut flags: ResMut<GameFlags>,
+    mut choice_events: EventWriter<TextBoxChoiceMade>,
+    mut finished_events: EventWriter<TextBoxFinished>,
+) {
+    for &DialogueChoiceConfirmed { text_box, index } in confirmed_events.read() {
+        let Ok((mut textbox, style, children)) = textbox_query.get_mut(text_box) else {
+            continue;
+        };
+        let Some(choice) = textbox.choices.get(index).cloned() else {
+            continue;
+        };
+        if !textbox.choices_visible {
+            // a click and a key press in the same frame, only the first one counts
+            continue;
+        }
+
+        for child in children.into_iter().flatten() {
+            if line_query.contains(*child) {
+                commands.entity(*child).despawn();
+            }
+        }
+        for (choices, _) in choices_query
+            .iter()
+            .filter(|(_, choices)| choices.text_box == text_box)
+        {
+            commands.entity(choices).despawn();
+        }
+        for (flag, value) in choice.set {
+            flags.set(flag, value);
+        }
+        choice_events.write(TextBoxChoiceMade {
+            text_box,
+            choice_id: choice.id,
+        });
+
+        let next_conversation = choice.next.and_then(|next| {
+            dialogue_scripts
+                .get(&dialogue_assets.script)
+                .and_then(|script| script.conversation_for(&next, &flags))
+        });
+        match next_conversation {
+            Some(conversation) => textbox.start_conversation(&conversation, style),
+            None => {
+                textbox.choices_visible = false;
+                textbox.is_finished = true;
+                finished_events.write(TextBoxFinished { text_box });
+            }
+        }
+    }
+}
+
+/// [`InteractionPalette`] only reacts to the mouse, so show the keyboard selection the same way
+/// a hovered button looks.
+fn highlight_selected_choice(
+    textbox_query: Query<&TextBox>,
+    button_query: Query<&TextBoxChoiceButton>,
+    mut palette_query: Query<(
+        &Interaction,
+        &InteractionPalette,
+        &mut BackgroundColor,
+        &ChildOf,
+    )>,
+) {
+    for (interaction, palette, mut background, child_of) in &mut palette_query {
+        let Ok(button) = button_query.get(child_of.parent()) else {
+            continue;
+        };
+        let Ok(textbox) = textbox_query.get(button.text_box) else {
+            continue;
+        };
+        if *interaction == Interaction::None {
+            background.0 = if button.index == textbox.selected_choice {
+                palette.hovered
+            } else {
+                palette.none
+            };
+        }
+    }
+}
+
+/// Plays the open and close transitions of each [`TextBox`], starts closing boxes once their
+/// dialogue is finished, and despawns them when they've closed.
+fn animate_text_box_transitions(
+    mut commands: Commands,
+    mut textbox_query: Query<(
+        Entity,
+        &mut TextBox,
+        &TextBoxStyle,
+        &mut Transform,
+        &Children,
+    )>,
+    frame_query: Query<(&TextBoxMesh, &MeshMaterial2d<ColorMaterial>)>,
+    mut content_query: Query<&mut Visibility, Or<(With<TextLine>, With<TextBoxIndicator>)>>,
+    choices_query: Query<(Entity, &TextBoxChoices)>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut opened_events: EventWriter<TextBoxOpened>,
+    mut closed_events: EventWriter<TextBoxClosed>,
+    fast_forward: Res<DialogueFastForward>,
+    time: Res<Time>,
+) {
+    for (entity, mut textbox, style, mut transform, children) in &mut textbox_query {
+        if textbox.phase == TextBoxPhase::Open {
+            if !textbox.is_finished {
+                continue;
+            }
+            textbox.close();
+        }
+
+        let previous_openness = textbox.openness(style);
+        textbox.phase_time_s += time.delta_secs() * fast_forward.speed();
+        let openness = textbox.openness(style);
+
+        let frame_alpha = match style.transition {
+            TextBoxTransition::Fade => openness,
+            TextBoxTransition::Scale => {
+                transform.scale = Vec2::splat(openness).extend(1.);
+                1.
+            }
+            TextBoxTransition::Slide(offset) => {
+                // only move by the change, so the box can still be moved around while sliding
+                transform.translation += (offset * (previous_openness - openness)).extend(0.);
+                1.
+            }
+        };
+        for (mesh_info, material) in frame_query.iter_many(children) {
+            if let Some(material) = materials.get_mut(&material.0) {
+                // the material color is multiplied with the mesh's vertex colors
+                material
+                    .color
+                    .set_alpha(frame_alpha * mesh_info.visible_alpha);
+            }
+        }
+
+        if textbox.phase == TextBoxPhase::Closing {
+            if style.transition == TextBoxTransition::Fade {
+                // text can't fade along with the frame, so take it away straight away
+                let mut content = content_query.iter_many_mut(children);
+                while let Some(mut visibility) = content.fetch_next() {
+                    *visibility = Visibility::Hidden;
+                }
+            }
+            for (choices, _) in choices_query
+                .iter()
+                .filter(|(_, choices)| choices.text_box == entity)
+            {
+                commands.entity(choices).despawn();
+            }
+        }
+
+        if textbox.phase_time_s >= style.transition_time {
+            match textbox.phase {
+                TextBoxPhase::Opening => {
+                    textbox.phase = TextBoxPhase::Open;
+                    textbox.phase_time_s = 0.;
+                    opened_events.write(TextBoxOpened { text_box: entity });
+                }
+                TextBoxPhase::Closing => {
+                    commands.entity(entity).despawn();
+                    closed_events.write(TextBoxClosed { text_box: entity });
+                }
+                TextBoxPhase::Open => {}
+            }
+        }
+    }
+}
+
+const TRIANGLE_WOBBLE_SPEED: f32 = 4.;
+const TRIANGLE_WOBBLE_OFFSET: f32 = 3.;
+fn animate_text_box_indicator(
+    mut indicator_query: Query<(&mut Transform, &ChildOf), With<TextBoxIndicator>>,
+    textbox_query: Query<(&TextBoxStyle, &TextBoxMeshes)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    time: Res<Time>,
+) {
+    let t = (time.elapsed_secs() * TRIANGLE_WOBBLE_SPEED).sin();
+    for (mut transform, child_of) in indicator_query.iter_mut() {
+        let Ok((style, text_box_meshes)) = textbox_query.get(child_of.parent()) else {
+            continue;
+        };
+        if let Some(mesh) = meshes.get_mut(&text_box_meshes.indicator) {
+            mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, get_triangle_colors(style, t));
+        }
+        transform.translation.y = get_indicator_y(style, time.elapsed_secs());
+    }
+}
+
+/// Relative to the [`TextBox`].
+fn get_indicator_y(style: &TextBoxStyle, elapsed_secs: f32) -> f32 {
+    style.center().y - style.size.y / 2.0
+        + (elapsed_secs * TRIANGLE_WOBBLE_SPEED).sin() * TRIANGLE_WOBBLE_OFFSET
+}
+
+/// Types out each [`TextBoxText`] character by character, waiting on any `[pause]`s along the
+/// way. Every run of the line has a span for its revealed part and a transparent span for the
+/// rest, so the layout of the line doesn't shift around while it's being revealed.
+fn reveal_text_box_text(
+    mut text_query: Query<(Entity, &mut TextBoxText)>,
+    mut writer: Text2dWriter,
+    fast_forward: Res<DialogueFastForward>,
+    text_speed: Res<TextSpeed>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_secs() * fast_forward.speed();
+    for (entity, mut text_info) in text_query.iter_mut() {
+        if text_info.is_fully_revealed {
+            // skip if we're already done typing
+            continue;
+        }
+        let total_chars = text_info.char_count;
+        if text_info.pause_remaining_s > 0. {
+            text_info.pause_remaining_s -= delta;
+        } else {
+            text_info.reveal_progress +=
+                delta * text_info.chars_per_second * text_speed.multiplier();
+            let mut revealed_chars = (text_info.reveal_progress as usize).min(total_chars);
+            if let Some(&(pause_index, pause_s)) = text_info.markup.pauses.get(text_info.next_pause)
+                && pause_index <= revealed_chars
+            {
+                // hold the reveal right where the pause is
+                revealed_chars = pause_index;
+                text_info.reveal_progress = pause_index as f32;
+                text_info.pause_remaining_s = pause_s;
+                text_info.next_pause += 1;
+            }
+            text_info.revealed_chars = text_info.revealed_chars.max(revealed_chars);
+        }
+
+        let mut run_start = 0;
+        for (i, run) in text_info.markup.runs.iter().enumerate() {
+            let run_revealed = text_info.revealed_chars.saturating_sub(run_start);
+            let split_at = run
+                .text
+                .char_indices()
+                .nth(run_revealed)
+                .map_or(run.text.len(), |(i, _)| i);
+            let (revealed, hidden) = run.text.split_at(split_at);
+            *writer.text(entity, 1 + 2 * i) = revealed.to_string();
+            *writer.text(entity, 2 + 2 * i) = hidden.to_string();
+            run_start += run.text.chars().count();
+        }
+
+        text_info.is_fully_revealed = text_info.revealed_chars == total_chars
+            && text_info.pause_remaining_s <= 0.
+            && text_info.next_pause >= text_info.markup.pauses.len();
+    }
+}
+
+/// Shows each [`TextBoxGlyph`] once the typewriter reaches it and moves it around according to
+/// its [`TextEffect`].
+fn animate_text_box_glyphs(
+    mut glyph_query: Query<(&TextBoxGlyph, &ChildOf, &mut Transform, &mut TextColor)>,
+    text_query: Query<&TextBoxText>,
+    time: Res<Time>,
+) {
+    let mut rng = rand::thread_rng();
+    for (glyph, child_of, mut transform, mut text_color) in &mut glyph_query {
+        let Ok(text_info) = text_query.get(child_of.parent()) else {
+            continue;
+        };
+        text_color.0 = if glyph.char_index < text_info.revealed_chars {
+            glyph.color
+        } else {
+            Color::NONE
+        };
+        let offset = match glyph.effect {
+            TextEffect::Wave => Vec2::new(
+                0.,
+                (time.elapsed_secs() * WAVE_SPEED + glyph.char_index as f32 * WAVE_CHAR_PHASE)
+                    .sin()
+                    * WAVE_AMPLITUDE,
+            ),
+            TextEffect::Shake => Vec2::new(
+                rng.gen_range(-SHAKE_AMPLITUDE..=SHAKE_AMPLITUDE),
+                rng.gen_range(-SHAKE_AMPLITUDE..=SHAKE_AMPLITUDE),
+            ),
+        };
+        transform.translation = glyph.base_translation + offset.extend(0.);
+    }
+}
+
+/// Fast text would otherwise play a blip for nearly every frame.
+const VOICE_BLIP_INTERVAL: f32 = 0.07;
+/// Plays the [`TextBoxText::voice`] as new characters are revealed, at most once every
+/// [`VOICE_BLIP_INTERVAL`].
+fn play_voice_blips(
+    mut commands: Commands,
+    mut text_query: Query<&mut TextBoxText>,
+    time: Res<Time>,
+) {
+    let mut rng = rand::thread_rng();
+    for mut text_info in &mut text_query {
+        let Some(voice) = &text_info.voice else {
+            continue;
+        };
+        let newly_revealed = text_info
+            .revealed_chars
+            .saturating_sub(text_info.voiced_chars);
+        // spaces shouldn't make a sound
+        let speaking = text_info
+            .markup
+            .runs
+            .iter()
+            .flat_map(|run| run.text.chars())
+            .skip(text_info.voiced_chars)
+            .take(newly_revealed)
+            .any(|c| !c.is_whitespace());
+        let blip = (speaking && text_info.time_since_blip_s >= VOICE_BLIP_INTERVAL).then(|| {
+            let pitch = voice.pitch + rng.gen_range(-voice.pitch_variation..=voice.pitch_variation);
+            (voice.sound_handle.clone(), pitch.max(0.1))
+        });
+
+        text_info.voiced_chars = text_info.revealed_chars;
+        text_info.time_since_blip_s += time.delta_secs();
+        if let Some((sound, pitch)) = blip {
+            text_info.time_since_blip_s = 0.;
+            commands
+                .spawn(sound_effect(sound))
+                .insert((PlaybackSettings::DESPAWN.with_speed(pitch), DucksMusic));
+        }
+    }
+}
+
+pub const TEXTBOX_OFFSET_FROM_CENTER_Y: f32 = -150.;
+pub const TEXTBOX_BG_ALPHA: f32 = 1.0;
+
+pub const TRIANGLE_INDICATOR_WIDTH: f32 = 34.;
+pub const TRIANGLE_INDICATOR_HEIGHT: f32 = 28.;
+
+pub const BOX_BG_Z: f32 = 1.;
+pub const BOX_BG_SHADOW_Z: f32 = 0.9;
+pub const TEXT_SHADOW_Z: f32 = 1.1;
+pub const TEXT_Z: f32 = 1.2;
+pub const TRIANGLE_MESH_Z: f32 = 1.3;
+pub const PORTRAIT_Z: f32 = 1.1;
+pub const NAME_PLATE_Z: f32 = 1.1;
+
+pub const PORTRAIT_SIZE: f32 = 150.;
+pub const PORTRAIT_PADDING: f32 = 15.;
+
+pub const NAME_PLATE_HEIGHT: f32 = 40.;
+pub const NAME_PLATE_INSET: f32 = 20.;
+pub const NAME_PLATE_PADDING: f32 = 14.;
+pub const NAME_PLATE_FONT_SIZE: f32 = 22.;
+
+pub const CHOICES_GAP: f32 = 10.;
+
+pub const WAVE_SPEED: f32 = 8.;
+pub const WAVE_AMPLITUDE: f32 = 4.;
+/// How far along the wave each following character is, in radians.
+pub const WAVE_CHAR_PHASE: f32 = 0.6;
+pub const SHAKE_AMPLITUDE: f32 = 1.5;
+
+pub const TEXT_REVEAL_CHARS_PER_SECOND: f32 = 30.;
+
+/// A text box at `translation`, with its [`TextBoxStyle::anchor`] deciding which part of the box
+/// ends up there. It starts out closed and opens with its [`TextBoxStyle::transition`].
+pub fn text_box(
+    text_box: TextBox,
+    style: TextBoxStyle,
+    translation: Vec2,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+) -> impl Bundle {
+    let text_box_meshes = TextBoxMeshes::new(&style, meshes, materials);
+    // each part of the frame fades in separately, so they get their own material
+    let mut faded_out_material = || {
+        materials.add(ColorMaterial {
+            color: Color::WHITE.with_alpha(0.),
+            ..default()
+        })
+    };
+    let center = style.center();
+    let mut transform = Transform::from_translation(translation.extend(0.));
+    match style.transition {
+        TextBoxTransition::Fade => {}
+        TextBoxTransition::Scale => transform.scale = Vec3::new(0., 0., 1.),
+        TextBoxTransition::Slide(offset) => transform.translation += offset.extend(0.),
+    }
+    (
+        text_box,
+        transform,
+        children![
+            (
+                Mesh2d(text_box_meshes.frame_shadow.clone()),
+                MeshMaterial2d(faded_out_material()),
+                Transform::from_translation(Vec3::new(
+                    center.x + style.frame_shadow_offset,
+                    center.y - style.frame_shadow_offset,
+                    BOX_BG_SHADOW_Z
+                ))
+                .with_scale(Vec3::splat(1.)),
+                TextBoxMesh::new(style.frame_shadow_alpha, false),
+            ),
+            (
+                Mesh2d(text_box_meshes.frame.clone()),
+                MeshMaterial2d(faded_out_material()),
+                Transform::from_translation(center.extend(BOX_BG_Z)).with_scale(Vec3::splat(1.)),
+                TextBoxMesh::new(TEXTBOX_BG_ALPHA, true),
+            ),
+        ],
+        text_box_meshes,
+        style,
+    )
+}
+
+fn text_line(
+    source: &str,
+    style: &TextBoxStyle,
+    has_portrait: bool,
+    voice: Option<DialogueVoice>,
+    chars_per_second: f32,
+) -> impl Bundle {
+    let offset_x = if has_portrait {
+        (PORTRAIT_SIZE + PORTRAIT_PADDING) / 2.0
+    } else {
+        0.
+    };
+    let markup = MarkupText::parse(source);
+    (
+        TextLine,
+        // everything on the line is laid out relative to the center of the frame
+        Transform::from_translation(style.center().extend(0.)),
+        Visibility::default(),
+        children![
+            (
+                // the shadow ignores any colors from the markup
+                text_layer(
+                    markup.clone(),
+                    style,
+                    has_portrait,
+                    None,
+                    chars_per_second,
+                    Some(style.text_shadow_color)
+                ),
+                Transform::from_translation(Vec3::new(
+                    offset_x + style.text_shadow_offset,
+                    -style.text_shadow_offset,
+                    TEXT_SHADOW_Z
+                ))
+                .with_scale(Vec3::splat(1.)),
+            ),
+            (
+                // only one of the layers should make a sound
+                text_layer(markup, style, has_portrait, voice, chars_per_second, None),
+                Transform::from_translation(Vec3::new(offset_x, 0., TEXT_Z))
+                    .with_scale(Vec3::splat(1.)),
+            ),
+        ],
+    )
+}
+
+/// One copy of a line of text. Each run of the markup gets a revealed and a hidden span, and
+/// every character of a run with a [`TextEffect`] gets its own [`TextBoxGlyph`] on top.
+fn text_layer(
+    markup: MarkupText,
+    style: &TextBoxStyle,
+    has_portrait: bool,
+    voice: Option<DialogueVoice>,
+    chars_per_second: f32,
+    color_override: Option<Color>,
+) -> impl Bundle {
+    let default_color = color_override.unwrap_or(style.text_color);
+    let run_color = move |run_color: Option<Color>| {
+        color_override.unwrap_or(run_color.unwrap_or(default_color))
+    };
+    let text_font = style.text_font(style.font_size);
+    let spans: Vec<_> = markup
+        .runs
+        .iter()
+        .flat_map(|run| {
+            let revealed_color = if run.style.effect.is_some() {
+                // drawn by the glyphs instead
+                Color::NONE
+            } else {
+                run_color(run.style.color)
+            };
+            [
+                (
+                    TextSpan::default(),
+                    text_font.clone(),
+                    TextColor(revealed_color),
+                ),
+                (
+                    TextSpan::new(run.text.clone()),
+                    text_font.clone(),
+                    TextColor(Color::NONE),
+                ),
+            ]
+        })
+        .collect();
+
+    // Text is assumed to be monospaced and rows are split with explicit line breaks, so every
+    // character's position can be worked out from its row and column.
+    let area = text_area_size(style, has_portrait);
+    let char_width = style.font_size * style.char_width;
+    let line_height = style.font_size * style.line_height;
+    let mut glyphs = Vec::new();
+    let (mut row, mut column, mut char_index) = (0, 0, 0);
+    for run in &markup.runs {
+        for c in run.text.chars() {
+            if c == '\n' {
+                row += 1;
+                column = 0;
+            } else {
+                if let Some(effect) = run.style.effect
+                    && !c.is_whitespace()
+                {
+                    let base_translation = Vec3::new(
+                        -area.x / 2.0 + (column as f32 + 0.5) * char_width,
+                        area.y / 2.0 - (row as f32 + 0.5) * line_height,
+                        0.,
+                    );
+                    glyphs.push((
+                        Text2d::new(c.to_string()),
+                        text_font.clone(),
+                        TextColor(Color::NONE),
+                        Transform::from_translation(base_translation),
+                        TextBoxGlyph {
+                            char_index,
+                            effect,
+                            color: run_color(run.style.color),
+                            base_translation,
+                        },
+                    ));
+                }
+                column += 1;
+            }
+            char_index += 1;
+        }
+    }
+
+    (
+        Text2d::default(),
+        text_font,
+        TextLayout::new(JustifyText::Left, LineBreak::WordBoundary),
+        TextBounds::from(area),
+        TextColor(default_color),
+        TextBoxText {
+            voice,
+            ..TextBoxText::new(markup, chars_per_second)
+        },
+        Children::spawn((SpawnIter(spans.into_iter()), SpawnIter(glyphs.into_iter()))),
+    )
+}
+
+/// The space available for text inside the frame, leaving room for a portrait if there is one.
+fn text_area_size(style: &TextBoxStyle, has_portrait: bool) -> Vec2 {
+    let portrait_width = if has_portrait {
+        PORTRAIT_SIZE + PORTRAIT_PADDING
+    } else {
+        0.
+    };
+    style.size - 2.0 * (style.border_thickness + style.text_padding) - Vec2::new(portrait_width, 0.)
+}
+
+/// Splits `lines` into pages, returning them along with the index of the line each came from.
+fn paginate_lines(lines: &[DialogueLine], style: &TextBoxStyle) -> (Vec<DialogueLine>, Vec<usize>) {
+    lines
+        .iter()
+        .enumerate()
+        .flat_map(|(index, line)| {
+            paginate(&line.text, style, line.portrait_image.is_some())
+                .into_iter()
+                .map(move |page| {
+                    let page = DialogueLine {
+                        text: page,
+                        ..line.clone()
+                    };
+                    (page, index)
+                })
+        })
+        .unzip()
+}
+
+/// Word wraps `text` to the text area and splits it into pages that fit in the box. Text is
+/// assumed to be monospaced, so the wrapping here matches what [`TextBounds`] ends up doing; the
+/// rows are joined with explicit line breaks anyway so the two can't disagree.
+fn paginate(text: &str, style: &TextBoxStyle, has_portrait: bool) -> Vec<String> {
+    let area = text_area_size(style, has_portrait);
+    let max_chars = (area.x / (style.font_size * style.char_width)) as usize;
+    let max_rows = (area.y / (style.font_size * style.line_height)) as usize;
+    markup::paginate(text, max_chars, max_rows)
+}
+
+fn text_box_next_indicator(
+    text_box_meshes: &TextBoxMeshes,
+    style: &TextBoxStyle,
+    spawn_time: f32,
+) -> impl Bundle {
+    (
+        Mesh2d(text_box_meshes.indicator.clone()),
+        MeshMaterial2d(text_box_meshes.indicator_material.clone()),
+        Transform::from_translation(Vec3::new(
+            style.center().x,
+            get_indicator_y(style, spawn_time),
+            TRIANGLE_MESH_Z,
+        ))
+        .with_scale(Vec3::splat(1.)),
+        TextBoxIndicator,
+    )
+}
+
+/// A tab on top of the frame's left edge showing who's talking.
+fn speaker_name_plate(speaker: String, style: &TextBoxStyle) -> impl Bundle {
+    // Text2d doesn't report its size until it's laid out, so estimate the width from the name.
+    let width = speaker.chars().count() as f32 * NAME_PLATE_FONT_SIZE * style.char_width
+        + NAME_PLATE_PADDING * 2.0;
+    (
+        Name::new("Speaker Name Plate"),
+        Sprite::from_color(style.name_plate_color, Vec2::new(width, NAME_PLATE_HEIGHT)),
+        Transform::from_translation(Vec3::new(
+            -style.size.x / 2.0 + NAME_PLATE_INSET + width / 2.0,
+            style.size.y / 2.0 + NAME_PLATE_HEIGHT / 2.0 - style.border_thickness,
+            NAME_PLATE_Z,
+        )),
+        children![(
+            Text2d::new(speaker),
+            style.text_font(NAME_PLATE_FONT_SIZE),
+            TextColor(style.text_color),
+            Transform::from_translation(Vec3::new(0., 0., TEXT_Z - NAME_PLATE_Z)),
+        )],
+    )
+}
+
+/// The speaker's portrait, inset on the left side of the frame.
+fn speaker_portrait(image: Handle<Image>, style: &TextBoxStyle) -> impl Bundle {
+    (
+        Name::new("Speaker Portrait"),
+        Sprite {
+            image,
+            custom_size: Some(Vec2::splat(PORTRAIT_SIZE)),
+            ..default()
+        },
+        Transform::from_translation(Vec3::new(
+            -style.size.x / 2.0 + style.border_thickness + PORTRAIT_PADDING + PORTRAIT_SIZE / 2.0,
+            0.,
+            PORTRAIT_Z,
+        )),
+    )
+}
+
+/// The choices sit in a column just above the top edge of their text box, see
+/// [`position_text_box_choices`].
+fn text_box_choices(text_box: Entity, width: f32, choices: &[DialogueChoice]) -> impl Bundle {
+    let buttons: Vec<_> = choices
+        .iter()
+        .enumerate()
+        .map(|(index, choice)| {
+            (
+                widget::button_wide(
+                    choice.text.clone(),
+                    move |_: Trigger<Pointer<Click>>,
+                          mut confirmed_events: EventWriter<DialogueChoiceConfirmed>| {
+                        confirmed_events.write(DialogueChoiceConfirmed { text_box, index });
+                    },
+                ),
+                TextBoxChoiceButton { text_box, index },
+            )
+        })
+        .collect();
+    (
+        Name::new("Text Box Choices"),
+        TextBoxChoices { text_box },
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Px(width),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(CHOICES_GAP),
+            ..default()
+        },
+        Pickable::IGNORE,
+        StateScoped(Screen::Gameplay),
+        Children::spawn(SpawnIter(buttons.into_iter())),
+    )
+}
+
+/// Keeps each set of [`TextBoxChoices`] lined up with its [`TextBox`] on screen.
+fn position_text_box_choices(
+    mut choices_query: Query<(&TextBoxChoices, &mut Node)>,
+    textbox_query: Query<(&GlobalTransform, &TextBoxStyle), With<TextBox>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+) {
+    let Ok((camera, camera_transform)) = camera_query.single() else {
+        return;
+    };
+    let Some(viewport_size) = camera.logical_viewport_size() else {
+        return;
+    };
+    for (choices, mut node) in &mut choices_query {
+        let Ok((textbox_transform, style)) = textbox_query.get(choices.text_box) else {
+            continue;
+        };
+        let top_center = textbox_transform
+            .transform_point((style.center() + Vec2::Y * style.size.y / 2.0).extend(0.));
+        let Ok(top_center) = camera.world_to_viewport(camera_transform, top_center) else {
+            continue;
+        };
+        node.left = Val::Px(top_center.x - style.size.x / 2.0);
+        node.bottom = Val::Px(viewport_size.y - top_center.y + CHOICES_GAP);
+    }
+}
+
+fn get_text_box_mesh(style: &TextBoxStyle, with_inner_vertices: bool) -> Mesh {
+    let half_height = style.size.y * 0.5;
+    let half_width = style.size.x * 0.5;
+    let inner_height = half_height - style.border_thickness;
+    let inner_width = half_width - style.border_thickness;
+    let mut vertices = vec![
+        [-half_width, half_height, 0.],
+        [half_width, half_height, 0.],
+        [half_width, -half_height, 0.],
+        [-half_width, -half_height, 0.],
+    ];
+    if with_inner_vertices {
+        vertices.extend([
+            [-inner_width, inner_height, 0.],
+            [inner_width, inner_height, 0.],
+            [inner_width, -inner_height, 0.],
+            [-inner_width, -inner_height, 0.],
+        ]);
+    }
+    let mesh = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, vertices)
+    .with_inserted_indices(Indices::U32(if with_inner_vertices {
+        vec![
+            0, 1, 4, 4, 1, 5, 5, 1, 2, 6, 5, 2, 3, 7, 6, 2, 3, 6, 3, 4, 7, 4, 3, 0,
+        ]
+    } else {
+        vec![0, 1, 2, 3, 0, 2]
+    }));
+    // Build vertex colors for the quad. One entry per vertex (the corners of the quad)
+    let corner_colors = style
+        .frame_colors
+        .map(|color| color.to_linear().to_f32_array());
+    let mut vertex_colors = corner_colors.to_vec();
+    if with_inner_vertices {
+        vertex_colors.extend(corner_colors);
+    }
+    mesh.with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, vertex_colors)
+}
+
+fn get_colored_triangle_mesh(style: &TextBoxStyle, t: f32) -> Mesh {
+    let half_height = TRIANGLE_INDICATOR_HEIGHT / 2.0;
+    let half_width = TRIANGLE_INDICATOR_WIDTH / 2.0;
+    let vertices = vec![
+        [-half_width, half_height, 0.0],
+        [half_width, half_height, 0.0],
+        [0.0, -half_height, 0.0],
+    ];
+    let mesh = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, vertices)
+    .with_inserted_indices(Indices::U32(vec![0, 1, 2]));
+    mesh.with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, get_triangle_colors(style, t))
+}
+
+/// Build vertex colors for the triangle. One entry per vertex (the corners of the triangle)
+fn get_triangle_colors(style: &TextBoxStyle, t: f32) -> Vec<[f32; 4]> {
+    let targets = if t >= 0. {
+        style.indicator_colors_up
+    } else {
+        style.indicator_colors_down
+    };
+    style
+        .indicator_colors
+        .iter()
+        .zip(targets)
+        .map(|(color, target)| {
+            color
+                .to_linear()
+                .mix(&target.to_linear(), t.abs())
+                .to_f32_array()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn animating_the_indicator_does_not_add_meshes() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()));
+        app.init_asset::<Mesh>();
+        app.init_asset::<ColorMaterial>();
+        app.add_systems(Update, animate_text_box_indicator);
+
+        let style = TextBoxStyle::default();
+        let world = app.world_mut();
+        let text_box_meshes = world.resource_scope(|world, mut meshes: Mut<Assets<Mesh>>| {
+            let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+            TextBoxMeshes::new(&style, &mut meshes, &mut materials)
+        });
+        let indicator = text_box_next_indicator(&text_box_meshes, &style, 0.);
+        let text_box = world
+            .spawn((TextBox::default(), style, text_box_meshes))
+            .id();
+        world.spawn((indicator, ChildOf(text_box)));
+        app.update();
+        let mesh_count = app.world().resource::<Assets<Mesh>>().len();
+
+        for _ in 0..10 {
+            app.update();
+        }
+        assert_eq!(app.world().resource::<Assets<Mesh>>().len(), mesh_count);
+    }
+
+    fn open_text_box() -> TextBox {
+        TextBox {
+            lines: vec![DialogueLine::default(), DialogueLine::default()],
+            last_text_index_displayed: Some(0),
+            indicator_visible: true,
+            phase: TextBoxPhase::Open,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn long_lines_start_on_their_first_page_only() {
+        let long = DialogueLine {
+            text: "word ".repeat(200),
+            ..default()
+        };
+        let short = DialogueLine {
+            text: "Hi".to_string(),
+            ..default()
+        };
+        let mut text_box = TextBox::new(vec![long, short], &TextBoxStyle::default());
+        let page_count = text_box.lines.len();
+        assert!(page_count > 2);
+
+        let mut starts = Vec::new();
+        for index in 0..page_count {
+            text_box.current_text_index = index;
+            if let Some(line) = text_box.line_starting_on_current_page() {
+                starts.push((index, line.text.clone()));
+            }
+        }
+        assert_eq!(
+            starts,
+            vec![(0, "word ".repeat(200)), (page_count - 1, "Hi".to_string())]
+        );
+    }
+
+    #[test]
+    fn only_the_newest_text_box_advances() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_event::<TextBoxFinished>();
+        app.add_systems(Update, focus_newest_text_box);
+
+        let older = app.world_mut().spawn(open_text_box()).id();
+        app.update();
+        let newer = app.world_mut().spawn(open_text_box()).id();
+        app.update();
+        app.world_mut().run_system_once(advance_text_box).unwrap();
+
+        let world = app.world();
+        assert!(!world.entity(older).contains::<FocusedTextBox>());
+        assert!(world.entity(newer).contains::<FocusedTextBox>());
+        assert_eq!(world.get::<TextBox>(older).unwrap().current_text_index, 0);
+        assert_eq!(world.get::<TextBox>(newer).unwrap().current_text_index, 1);
+
+        // the older box gets the focus back once the newer one is gone
+        app.world_mut().despawn(newer);
+        app.update();
+        assert!(app.world().entity(older).contains::<FocusedTextBox>());
+    }
+}
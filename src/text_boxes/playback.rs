@@ -0,0 +1,184 @@
+//! How dialogue moves along besides the player confirming each line: on its own after a delay,
+//! or faster while the player holds confirm.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{AppSystems, PausableSystems, input::ActionInput};
+
+use super::{
+    FocusedTextBox, TextBox, TextBoxFinished, TextBoxIndicator, TextBoxText, TextLine,
+    confirm_held, dismiss_line,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<DialoguePlayback>();
+    app.init_resource::<DialoguePlayback>();
+    app.register_type::<TextSpeed>();
+    app.init_resource::<TextSpeed>();
+    app.init_resource::<DialogueFastForward>();
+
+    app.add_systems(
+        Update,
+        (
+            record_fast_forward.in_set(AppSystems::RecordInput),
+            auto_advance_text_boxes.in_set(AppSystems::Update),
+        )
+            .in_set(PausableSystems),
+    );
+}
+
+/// The dialogue playback setting, changed in the settings menu.
+#[derive(Resource, Reflect, Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[reflect(Resource)]
+pub enum DialoguePlayback {
+    /// Every line waits for the player to confirm.
+    #[default]
+    Manual,
+    /// Lines move on by themselves, after a delay that grows with their length.
+    Auto,
+    /// Like [`Self::Manual`], but holding confirm speeds the text up and skips through lines.
+    FastForward,
+}
+
+impl DialoguePlayback {
+    const ALL: [Self; 3] = [Self::Manual, Self::Auto, Self::FastForward];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Manual => "Manual",
+            Self::Auto => "Auto",
+            Self::FastForward => "Hold to skip",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|mode| *mode == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn previous(self) -> Self {
+        let index = Self::ALL.iter().position(|mode| *mode == self).unwrap_or(0);
+        Self::ALL[(index + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+/// How quickly dialogue is typed out, changed in the settings menu.
+#[derive(Resource, Reflect, Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[reflect(Resource)]
+pub enum TextSpeed {
+    Slow,
+    #[default]
+    Normal,
+    Fast,
+}
+
+impl TextSpeed {
+    const ALL: [Self; 3] = [Self::Slow, Self::Normal, Self::Fast];
+
+    /// How much faster than normal text is revealed.
+    pub(super) fn multiplier(self) -> f32 {
+        match self {
+            Self::Slow => 0.5,
+            Self::Normal => 1.,
+            Self::Fast => 2.,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Slow => "Slow",
+            Self::Normal => "Normal",
+            Self::Fast => "Fast",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|speed| *speed == self)
+            .unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn previous(self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|speed| *speed == self)
+            .unwrap_or(0);
+        Self::ALL[(index + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+/// Whether dialogue is being fast-forwarded this frame.
+#[derive(Resource, Default)]
+pub(super) struct DialogueFastForward(bool);
+
+impl DialogueFastForward {
+    /// How much faster text is revealed and transitions play.
+    pub(super) fn speed(&self) -> f32 {
+        if self.0 { FAST_FORWARD_SPEED } else { 1. }
+    }
+}
+
+const FAST_FORWARD_SPEED: f32 = 4.;
+
+fn record_fast_forward(
+    playback: Res<DialoguePlayback>,
+    input: ActionInput,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut fast_forward: ResMut<DialogueFastForward>,
+) {
+    fast_forward.0 = *playback == DialoguePlayback::FastForward && confirm_held(&input, &mouse);
+}
+
+/// How long a line stays up once it's revealed in [`DialoguePlayback::Auto`], plus
+/// [`AUTO_ADVANCE_DELAY_PER_CHAR`] for each of its characters.
+const AUTO_ADVANCE_DELAY: f32 = 1.;
+const AUTO_ADVANCE_DELAY_PER_CHAR: f32 = 0.05;
+/// How long a line stays up once it's revealed while fast-forwarding.
+const FAST_FORWARD_ADVANCE_DELAY: f32 = 0.1;
+
+/// Dismisses lines that have been showing their indicator for long enough. Choices still wait
+/// for the player.
+fn auto_advance_text_boxes(
+    mut commands: Commands,
+    playback: Res<DialoguePlayback>,
+    fast_forward: Res<DialogueFastForward>,
+    mut textbox_query: Query<(Entity, &mut TextBox, Option<&Children>, Has<FocusedTextBox>)>,
+    children_query: Query<&Children>,
+    text_query: Query<&TextBoxText>,
+    line_query: Query<(), Or<(With<TextLine>, With<TextBoxIndicator>)>>,
+    mut finished_events: EventWriter<TextBoxFinished>,
+    time: Res<Time>,
+) {
+    for (entity, mut textbox, children, focused) in &mut textbox_query {
+        if !textbox.indicator_visible {
+            continue;
+        }
+        textbox.indicator_time_s += time.delta_secs();
+        // holding confirm only skips through the box it would advance
+        let delay = if fast_forward.0 && focused {
+            FAST_FORWARD_ADVANCE_DELAY
+        } else if *playback == DialoguePlayback::Auto {
+            let char_count = text_query
+                .iter_many(children_query.iter_descendants(entity))
+                .map(|text| text.char_count)
+                .max()
+                .unwrap_or(0);
+            AUTO_ADVANCE_DELAY + char_count as f32 * AUTO_ADVANCE_DELAY_PER_CHAR
+        } else {
+            continue;
+        };
+        if textbox.indicator_time_s >= delay {
+            dismiss_line(
+                &mut commands,
+                entity,
+                &mut textbox,
+                children,
+                &line_query,
+                &mut finished_events,
+            );
+        }
+    }
+}
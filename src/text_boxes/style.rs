@@ -0,0 +1,171 @@
+//! The look of a text box, authored as `.text_box.ron` assets so different characters or menus
+//! can have their own style.
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    color::palettes::css::{BLACK, GHOST_WHITE},
+    prelude::*,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<TextBoxStyle>();
+    app.init_asset_loader::<TextBoxStyleLoader>();
+    app.register_type::<TextBoxStyle>();
+    app.register_type::<TextBoxTransition>();
+}
+
+/// How a text box appears when it opens, played in reverse when it closes.
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+pub enum TextBoxTransition {
+    /// The frame fades in. The text is hidden as soon as the box starts closing.
+    #[default]
+    Fade,
+    /// The box grows out of its [`Transform`].
+    Scale,
+    /// The box slides in from this offset.
+    Slide(Vec2),
+}
+
+/// Colors, dimensions and fonts of a [`TextBox`](super::TextBox). Any field left out of a
+/// `.text_box.ron` file keeps its default, which is the red frame.
+#[derive(Asset, Component, Reflect, Debug, Clone, Deserialize)]
+#[reflect(Component)]
+#[serde(default)]
+pub struct TextBoxStyle {
+    /// Outer size of the frame.
+    pub size: Vec2,
+    /// Which point of the box sits at its [`Transform`], from `(-0.5, -0.5)` for the bottom left
+    /// corner to `(0.5, 0.5)` for the top right. `(0, 0)` is the center.
+    pub anchor: Vec2,
+    pub border_thickness: f32,
+    pub transition: TextBoxTransition,
+    /// How long opening and closing take, in seconds.
+    pub transition_time: f32,
+    /// Frame corner colors, clockwise from the top left.
+    pub frame_colors: [Color; 4],
+    pub frame_shadow_alpha: f32,
+    pub frame_shadow_offset: f32,
+    /// Corner colors of the "next line" indicator while it's at rest.
+    pub indicator_colors: [Color; 3],
+    /// What the indicator's colors blend towards as it bobs up.
+    pub indicator_colors_up: [Color; 3],
+    /// What the indicator's colors blend towards as it bobs down.
+    pub indicator_colors_down: [Color; 3],
+    pub name_plate_color: Color,
+    /// Path to the font, relative to the assets folder. Bevy's default font is used otherwise.
+    pub font: Option<String>,
+    /// The loaded [`Self::font`], filled in by the loader.
+    #[serde(skip)]
+    pub font_handle: Handle<Font>,
+    pub font_size: f32,
+    /// Glyph advance of the font, as a fraction of the font size. Text is laid out assuming the
+    /// font is monospaced.
+    pub char_width: f32,
+    /// Line height of the font, as a fraction of the font size.
+    pub line_height: f32,
+    /// Space between the inside of the frame and the text.
+    pub text_padding: f32,
+    /// Used for text that doesn't set its own color with markup.
+    pub text_color: Color,
+    pub text_shadow_color: Color,
+    pub text_shadow_offset: f32,
+}
+
+impl Default for TextBoxStyle {
+    fn default() -> Self {
+        Self {
+            size: Vec2::new(700., 200.),
+            anchor: Vec2::ZERO,
+            border_thickness: 10.,
+            transition: TextBoxTransition::Fade,
+            transition_time: 0.125,
+            frame_colors: [
+                LinearRgba::new(0.95, 0.05, 0.2, 1.0).into(),
+                LinearRgba::new(0.97, 0.0, 0.17, 1.0).into(),
+                LinearRgba::new(0.98, 0.0, 0.1, 1.0).into(),
+                LinearRgba::new(0.92, 0.1, 0.1, 1.0).into(),
+            ],
+            frame_shadow_alpha: 0.2,
+            frame_shadow_offset: 8.,
+            indicator_colors: [
+                LinearRgba::new(0.05, 0.2, 0.95, 1.0).into(),
+                LinearRgba::new(0.0, 0.17, 0.97, 1.0).into(),
+                LinearRgba::new(0.0, 0.1, 0.98, 1.0).into(),
+            ],
+            indicator_colors_up: [
+                LinearRgba::new(0.05, 0.95, 0.2, 1.0).into(),
+                LinearRgba::new(0.0, 0.97, 0.17, 1.0).into(),
+                LinearRgba::new(0.0, 0.98, 0.1, 1.0).into(),
+            ],
+            indicator_colors_down: [
+                LinearRgba::new(0.95, 0.90, 0.2, 1.0).into(),
+                LinearRgba::new(0.97, 0.95, 0.17, 1.0).into(),
+                LinearRgba::new(0.98, 0.97, 0.3, 1.0).into(),
+            ],
+            name_plate_color: LinearRgba::new(0.95, 0.05, 0.2, 1.0).into(),
+            font: None,
+            font_handle: Handle::default(),
+            font_size: 25.,
+            char_width: 0.6,
+            // matches Bevy's default `LineHeight`
+            line_height: 1.2,
+            text_padding: 15.,
+            text_color: GHOST_WHITE.into(),
+            text_shadow_color: BLACK.into(),
+            text_shadow_offset: 2.,
+        }
+    }
+}
+
+impl TextBoxStyle {
+    /// Where the center of the frame is, relative to the text box's [`Transform`].
+    pub fn center(&self) -> Vec2 {
+        -self.anchor * self.size
+    }
+
+    pub fn text_font(&self, font_size: f32) -> TextFont {
+        TextFont {
+            font: self.font_handle.clone(),
+            font_size,
+            ..default()
+        }
+    }
+}
+
+#[derive(Default)]
+struct TextBoxStyleLoader;
+
+#[derive(Debug, Error)]
+enum TextBoxStyleLoaderError {
+    #[error("could not read text box style: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse text box style: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for TextBoxStyleLoader {
+    type Asset = TextBoxStyle;
+    type Settings = ();
+    type Error = TextBoxStyleLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let mut style: TextBoxStyle = ron::de::from_bytes(&bytes)?;
+        if let Some(path) = &style.font {
+            style.font_handle = load_context.load(path.clone());
+        }
+        Ok(style)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["text_box.ron"]
+    }
+}